@@ -33,7 +33,12 @@ fn main() -> wasmtime::Result<()> {
     for plug in plugs.items().iter() {
         println!("[INFO]: '{}' metadata:", plug.name);
         println!("[INFO]:     exports: {:?}", plug.exports);
-        println!("[INFO]:     imports: {:?}\n", plug.imports);
+        let imports = plug
+            .imports
+            .iter()
+            .map(|imp| imp.to_string())
+            .collect::<Vec<_>>();
+        println!("[INFO]:     imports: {imports:?}\n");
     }
 
     println!("[INFO]: Starting to link...");
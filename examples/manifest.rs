@@ -0,0 +1,35 @@
+use wlug::{
+    wasmtime::{self, Caller, Engine},
+    PlugContext, Plugs,
+};
+
+fn main() -> wasmtime::Result<()> {
+    let engine = Engine::default();
+    let mut plugs = Plugs::new(&engine, ());
+
+    // Host functions need to be added before the manifest is loaded, since loading it also links the plugins
+    plugs.add_host_fn("print", |c: Caller<'_, PlugContext<()>>, a: i32| {
+        println!("[core::print]: plug{}: {a}", c.data().0 + 1);
+    });
+    plugs.add_host_fn(
+        "print2",
+        |c: Caller<'_, PlugContext<()>>, x: i32, y: i32| {
+            println!("[core::print2]: plug{}: {x},{y}", c.data().0 + 1);
+        },
+    );
+
+    // Loads, links and initializes the plugins listed in the manifest
+    let report = plugs.load_manifest("examples/plugins.toml", &engine)?;
+    for skipped in report.skipped.iter() {
+        println!(
+            "[WARN]: Skipped '{}': {}",
+            skipped.path.display(),
+            skipped.reason
+        );
+    }
+
+    println!("[INFO]: Calling plug1.plug1 with args: 10\nPlugin output:");
+    plugs.call::<_, ()>("plug1", "plug1", 10i32)?;
+
+    Ok(())
+}
@@ -5,6 +5,11 @@ pub extern "C" fn __name() -> *const u8 {
 
 extern "C" {
     fn print(a: i32);
+}
+
+// Imports from a module named after a dependency are only resolved from that dependency
+#[link(wasm_import_module = "plug2")]
+extern "C" {
     fn plug2(a: i32);
 }
 
@@ -0,0 +1,203 @@
+use std::{
+    collections::HashMap,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use wasmtime::{Engine, Module};
+
+use crate::{ArchiveError, ConfigValue, ManifestError, PlugId, Plugs};
+
+/// Name of the manifest inside of a `.wlug` archive
+pub const ARCHIVE_MANIFEST: &str = "wlug.toml";
+
+/// Directory inside of a `.wlug` archive whose files are exposed to the plugin as assets
+pub const ARCHIVE_ASSETS_DIR: &str = "assets";
+
+/// Maximum size in bytes of a single file inside of a `.wlug` archive
+pub const MAX_ARCHIVE_ENTRY_SIZE: u64 = 64 * 1024 * 1024;
+
+/// Maximum size in bytes of all files inside of a `.wlug` archive together
+pub const MAX_ARCHIVE_SIZE: u64 = 256 * 1024 * 1024;
+
+/// The manifest of a `.wlug` archive, see [`Plugs::load_archive`]
+///
+/// ```toml
+/// module = "plugin.wasm"
+///
+/// [metadata]
+/// description = "Adds more enemies"
+///
+/// [config]
+/// spawn_rate = 0.5
+/// ```
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ArchiveManifest {
+    /// Path of the plugin's module inside of the archive, defaults to `plugin.wasm`
+    #[serde(default = "default_module")]
+    pub module: String,
+    /// Free-form metadata for the host
+    #[serde(default)]
+    pub metadata: HashMap<String, String>,
+    /// Default configuration of the plugin, see [`Plugs::set_config`]
+    #[serde(default)]
+    pub config: HashMap<String, ConfigValue>,
+}
+
+fn default_module() -> String {
+    "plugin.wasm".to_string()
+}
+
+impl Default for ArchiveManifest {
+    fn default() -> Self {
+        Self {
+            module: default_module(),
+            metadata: HashMap::new(),
+            config: HashMap::new(),
+        }
+    }
+}
+
+/// Information about the archive a plugin was loaded from, see [`Plugs::archive`]
+#[derive(Clone, Debug)]
+pub struct PlugArchive {
+    pub path: PathBuf,
+    pub manifest: ArchiveManifest,
+    /// Names of the assets in the archive, relative to its `assets` directory
+    pub assets: Vec<String>,
+}
+
+/// The contents of an archive that are needed after its module was compiled
+pub(crate) struct ArchiveContents {
+    pub(crate) archive: PlugArchive,
+    pub(crate) assets: HashMap<String, Vec<u8>>,
+}
+
+/// Read a `.wlug` archive and compile its module
+pub(crate) fn read_archive(
+    path: &Path,
+    engine: &Engine,
+) -> wasmtime::Result<(Module, ArchiveContents)> {
+    let mut files = HashMap::new();
+    let mut total = 0;
+    let mut archive = tar::Archive::new(std::fs::File::open(path)?);
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        let name = entry.path()?.to_string_lossy().replace('\\', "/");
+        let remaining = MAX_ARCHIVE_SIZE - total;
+        let too_large = || {
+            if remaining < MAX_ARCHIVE_ENTRY_SIZE {
+                ArchiveError::TooLarge {
+                    path: path.to_path_buf(),
+                    limit: MAX_ARCHIVE_SIZE,
+                }
+            } else {
+                ArchiveError::EntryTooLarge {
+                    path: path.to_path_buf(),
+                    entry: name.clone(),
+                    limit: MAX_ARCHIVE_ENTRY_SIZE,
+                }
+            }
+        };
+        // The size in the header is checked before anything is allocated, reading is limited as well in case it's wrong
+        let limit = MAX_ARCHIVE_ENTRY_SIZE.min(remaining);
+        if entry.size() > limit {
+            return Err(too_large().into());
+        }
+        let mut data = Vec::new();
+        (&mut entry).take(limit + 1).read_to_end(&mut data)?;
+        if data.len() as u64 > limit {
+            return Err(too_large().into());
+        }
+        total += data.len() as u64;
+        files.insert(name.trim_start_matches("./").to_string(), data);
+    }
+
+    let manifest = match files.remove(ARCHIVE_MANIFEST) {
+        Some(text) => {
+            let manifest_path = path.join(ARCHIVE_MANIFEST);
+            let text = String::from_utf8(text).map_err(|e| ManifestError {
+                path: manifest_path.clone(),
+                message: e.to_string(),
+            })?;
+            toml::from_str(&text).map_err(|e| ManifestError {
+                path: manifest_path,
+                message: e.message().to_string(),
+            })?
+        }
+        None => ArchiveManifest::default(),
+    };
+
+    let bin = files
+        .remove(&manifest.module)
+        .ok_or_else(|| ArchiveError::MissingEntry {
+            path: path.to_path_buf(),
+            entry: manifest.module.clone(),
+        })?;
+    let module = crate::compile_module(&bin, Path::new(&manifest.module), engine)?;
+
+    let prefix = format!("{ARCHIVE_ASSETS_DIR}/");
+    let assets = files
+        .into_iter()
+        .filter_map(|(name, data)| Some((name.strip_prefix(&prefix)?.to_string(), data)))
+        .collect::<HashMap<_, _>>();
+    let mut names = assets.keys().cloned().collect::<Vec<_>>();
+    names.sort();
+
+    Ok((
+        module,
+        ArchiveContents {
+            archive: PlugArchive {
+                path: path.to_path_buf(),
+                manifest,
+                assets: names,
+            },
+            assets,
+        },
+    ))
+}
+
+impl<'a, T> Plugs<'a, T> {
+    /// Load a plugin from a `.wlug` archive and return its id (see `load_module`)
+    ///
+    /// A `.wlug` archive is an uncompressed tar archive that contains the plugin's module, an optional `wlug.toml`
+    /// manifest (see [`ArchiveManifest`]) and assets inside of an `assets` directory. The default configuration in the
+    /// manifest is applied to the plugin and its assets can be read by the plugin with the `asset_size` and `asset_read`
+    /// functions of [`crate::BUILTIN_MODULE`].
+    ///
+    /// # Errors
+    ///
+    /// - May return [`std::io::Error`]s from reading the archive.
+    /// - Returns [`ArchiveError`] if the archive doesn't contain the module or its files are larger than
+    ///   [`MAX_ARCHIVE_ENTRY_SIZE`] or [`MAX_ARCHIVE_SIZE`].
+    /// - Returns [`ManifestError`] if the archive's manifest is invalid.
+    /// - May return any error [`Plugs::load_module`] returns.
+    pub fn load_archive(
+        &mut self,
+        file_path: impl AsRef<Path>,
+        engine: &Engine,
+    ) -> wasmtime::Result<PlugId> {
+        let (module, contents) = read_archive(file_path.as_ref(), engine)?;
+        let id = self.load_module(module, engine)?;
+        self.add_archive(id, contents);
+        Ok(id)
+    }
+
+    /// Apply the default configuration and assets of an archive to the plugin `id`
+    pub(crate) fn add_archive(&mut self, id: PlugId, contents: ArchiveContents) {
+        self.config
+            .merge(id, contents.archive.manifest.config.clone());
+        self.assets.set(id, contents.assets);
+        self.archives.insert(id, contents.archive);
+    }
+
+    /// Returns information about the archive a plugin was loaded from, `None` if it wasn't loaded from an archive
+    pub fn archive(&self, name: &str) -> Option<&PlugArchive> {
+        self.archives.get(&self.get_id(name)?)
+    }
+}
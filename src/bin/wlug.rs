@@ -0,0 +1,208 @@
+use std::process::ExitCode;
+
+use wlug::{
+    wasmtime::{self, Engine, ExternType, Module},
+    ItemType, LinkError, Plugs, Val,
+};
+
+const USAGE: &str = "\
+Usage:
+    wlug inspect <plugin.wasm>
+    wlug check [--stub-host] <plugin.wasm>...
+    wlug call [--stub-host] <plugins-dir> <plugin> <function> [args]...
+
+Options:
+    --stub-host    Provide imports that no plugin exports as host functions that print their arguments and return zeroes";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let stub_host = args.iter().any(|arg| arg == "--stub-host");
+    let args = args
+        .iter()
+        .map(String::as_str)
+        .filter(|arg| *arg != "--stub-host")
+        .collect::<Vec<_>>();
+
+    let res = match args.as_slice() {
+        ["inspect", path] => inspect(path),
+        ["check", paths @ ..] if !paths.is_empty() => check(paths, stub_host),
+        ["call", dir, plug, func, call_args @ ..] => call(dir, plug, func, call_args, stub_host),
+        _ => {
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+
+    match res {
+        Ok(code) => code,
+        Err(e) => {
+            eprintln!("error: {e}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Print the metadata, exports and imports of a single plugin
+fn inspect(path: &str) -> wasmtime::Result<ExitCode> {
+    let engine = Engine::default();
+    let module = Module::from_file(&engine, path)?;
+    let mut plugs = Plugs::new(&engine, ());
+    let metadata = plugs.extract_metadata(&engine, &module, 0)?;
+
+    println!("name: {}", metadata.name);
+    let details = [
+        ("version", &metadata.version),
+        ("description", &metadata.description),
+        ("license", &metadata.license),
+        ("homepage", &metadata.homepage),
+    ];
+    for (key, value) in details {
+        if let Some(value) = value {
+            println!("{key}: {value}");
+        }
+    }
+    if !metadata.authors.is_empty() {
+        println!("authors: {}", metadata.authors.join(", "));
+    }
+    println!("deps:");
+    for dep in metadata.deps.iter() {
+        if metadata.optional_deps.contains(dep) {
+            println!("    {dep} (optional)");
+        } else {
+            println!("    {dep}");
+        }
+    }
+    println!("exports:");
+    for export in module.exports() {
+        println!("    {}: {}", export.name(), ItemType::from(&export.ty()));
+    }
+    println!("imports:");
+    for import in metadata.imports.iter() {
+        println!("    {import}: {}", ItemType::from(&import.ty));
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Load and link the given plugins and report every error
+fn check(paths: &[&str], stub_host: bool) -> wasmtime::Result<ExitCode> {
+    let engine = Engine::default();
+    let mut plugs = Plugs::new(&engine, ());
+    let mut ok = true;
+    for path in paths {
+        if let Err(e) = plugs.load(path, &engine) {
+            println!("error: couldn't load '{path}': {e}");
+            ok = false;
+        }
+    }
+    if stub_host {
+        add_host_stubs(&mut plugs);
+    }
+
+    let plan = plugs.plan_link();
+    for p_plan in plan.plugs.iter() {
+        for dep in p_plan.unused_deps.iter() {
+            println!(
+                "warning: '{}' doesn't import anything from '{dep}'",
+                p_plan.name
+            );
+        }
+        for dep in p_plan.missing_optional_deps.iter() {
+            println!(
+                "note: optional dependency '{dep}' of '{}' isn't loaded",
+                p_plan.name
+            );
+        }
+    }
+    for e in plan.errors() {
+        println!("error: {e}");
+        ok = false;
+    }
+    if plan.is_ok() {
+        if let Err(e) = plugs.link() {
+            println!("error: {e}");
+            ok = false;
+        }
+    }
+
+    if ok {
+        println!("{} plugin(s) linked successfully", plugs.items().len());
+        Ok(ExitCode::SUCCESS)
+    } else {
+        Ok(ExitCode::FAILURE)
+    }
+}
+
+/// Load all plugins in `dir`, then link and initialize them and call `func` in `plug`
+fn call(
+    dir: &str,
+    plug: &str,
+    func: &str,
+    call_args: &[&str],
+    stub_host: bool,
+) -> wasmtime::Result<ExitCode> {
+    let engine = Engine::default();
+    let mut plugs = Plugs::new(&engine, ());
+    plugs.load_dir(dir, &engine)?;
+    if stub_host {
+        add_host_stubs(&mut plugs);
+    }
+    plugs.link()?;
+    plugs.init()?;
+
+    let results = plugs.call_str(plug, func, call_args)?;
+    println!("{}", results.join(" "));
+
+    Ok(ExitCode::SUCCESS)
+}
+
+/// Add host functions for all function imports that can't be resolved, the stubs print their arguments and return zeroes
+fn add_host_stubs(plugs: &mut Plugs<()>) {
+    let mut stubbed = Vec::new();
+    for e in plugs.plan_link().errors() {
+        let unresolved_imports = if let LinkError::UnresolvedImports {
+            unresolved_imports, ..
+        } = e
+        {
+            unresolved_imports
+        } else {
+            continue;
+        };
+
+        for imp in unresolved_imports {
+            let ty = if let ExternType::Func(ty) = &imp.ty {
+                ty.clone()
+            } else {
+                continue;
+            };
+            let key = (imp.module.clone(), imp.name.clone());
+            if stubbed.contains(&key) {
+                continue;
+            }
+
+            let label = imp.to_string();
+            let defaults = ty
+                .results()
+                .map(|result| Val::default_for_ty(&result).unwrap_or(Val::I32(0)))
+                .collect::<Vec<_>>();
+            plugs.add_dynamic_host_fn(&imp.name, &imp.module, ty, move |_, args, results| {
+                let args = args.iter().map(fmt_val).collect::<Vec<_>>();
+                println!("[stub] {label}({})", args.join(", "));
+                results.copy_from_slice(&defaults);
+                Ok(())
+            });
+            stubbed.push(key);
+        }
+    }
+}
+
+fn fmt_val(val: &Val) -> String {
+    match val {
+        Val::I32(n) => n.to_string(),
+        Val::I64(n) => n.to_string(),
+        Val::F32(bits) => f32::from_bits(*bits).to_string(),
+        Val::F64(bits) => f64::from_bits(*bits).to_string(),
+        Val::V128(n) => format!("{:#x}", n.as_u128()),
+        _ => "<ref>".to_string(),
+    }
+}
@@ -0,0 +1,333 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use wasmtime::{Caller, Engine, ExternType, Func, FuncType, Memory, Store, ValType};
+
+use crate::{config::SharedConfig, PlugContext, PlugId, PlugImport};
+
+/// Import module of the functions that `wlug` provides to every plugin
+pub const BUILTIN_MODULE: &str = "wlug";
+
+/// Names of the functions inside [`BUILTIN_MODULE`]
+///
+/// - `has_dep(name_ptr: u32, name_len: u32) -> i32`: Returns 1 if the dependency with the given UTF-8 name is present, 0 otherwise.
+///   Mostly useful for optional dependencies.
+/// - `config_get_str(key_ptr: u32, key_len: u32, buf_ptr: u32, buf_len: u32) -> i32`: Copies the string value of the
+///   plugin's config key (see [`crate::Plugs::set_config`]) into the buffer, truncated to `buf_len` bytes. Returns the
+///   length of the whole value or -1 if the key isn't set or isn't a string.
+/// - `config_get_int(key_ptr: u32, key_len: u32, default: i64) -> i64`: Returns the integer value of the config key or
+///   `default` if the key isn't set or isn't an integer.
+/// - `config_get_float(key_ptr: u32, key_len: u32, default: f64) -> f64`: Returns the float (or integer) value of the
+///   config key or `default`.
+/// - `config_get_bool(key_ptr: u32, key_len: u32, default: i32) -> i32`: Returns 1 or 0 for the bool value of the
+///   config key or `default`.
+/// - `asset_size(name_ptr: u32, name_len: u32) -> i32`: Returns the size of an asset in the archive the plugin was loaded
+///   from (see `Plugs::load_archive`) or -1 if there is no such asset.
+/// - `asset_read(name_ptr: u32, name_len: u32, offset: u32, buf_ptr: u32, buf_len: u32) -> i32`: Copies the bytes of an
+///   asset starting at `offset` into the buffer and returns the number of copied bytes, or -1 if there is no such asset.
+pub const BUILTIN_FNS: &[&str] = &[
+    "has_dep",
+    "config_get_str",
+    "config_get_int",
+    "config_get_float",
+    "config_get_bool",
+    "asset_size",
+    "asset_read",
+];
+
+/// Returns whether `imp` refers to one of the functions in [`BUILTIN_FNS`]
+pub(crate) fn is_builtin(imp: &PlugImport) -> bool {
+    imp.module == BUILTIN_MODULE
+        && BUILTIN_FNS.contains(&imp.name.as_str())
+        && matches!(imp.ty, ExternType::Func(_))
+}
+
+/// Returns the type of the builtin function with the given name
+pub(crate) fn func_type(engine: &Engine, name: &str) -> Option<FuncType> {
+    match name {
+        "has_dep" => Some(FuncType::new(
+            engine,
+            [ValType::I32, ValType::I32],
+            [ValType::I32],
+        )),
+        "config_get_str" => Some(FuncType::new(
+            engine,
+            [ValType::I32, ValType::I32, ValType::I32, ValType::I32],
+            [ValType::I32],
+        )),
+        "config_get_int" => Some(FuncType::new(
+            engine,
+            [ValType::I32, ValType::I32, ValType::I64],
+            [ValType::I64],
+        )),
+        "config_get_float" => Some(FuncType::new(
+            engine,
+            [ValType::I32, ValType::I32, ValType::F64],
+            [ValType::F64],
+        )),
+        "config_get_bool" => Some(FuncType::new(
+            engine,
+            [ValType::I32, ValType::I32, ValType::I32],
+            [ValType::I32],
+        )),
+        "asset_size" => Some(FuncType::new(
+            engine,
+            [ValType::I32, ValType::I32],
+            [ValType::I32],
+        )),
+        "asset_read" => Some(FuncType::new(
+            engine,
+            [
+                ValType::I32,
+                ValType::I32,
+                ValType::I32,
+                ValType::I32,
+                ValType::I32,
+            ],
+            [ValType::I32],
+        )),
+        _ => None,
+    }
+}
+
+/// Create the builtin functions for the plugin `id` whose present (loaded) dependencies are `present_deps`
+pub(crate) fn funcs<T>(
+    store: &mut Store<PlugContext<T>>,
+    id: PlugId,
+    present_deps: Vec<String>,
+    config: SharedConfig,
+    assets: SharedAssets,
+    memories: SharedMemories,
+) -> Vec<(&'static str, Func)> {
+    let memory = PlugMemory { id, memories };
+
+    let mem = memory.clone();
+    let has_dep = Func::wrap(
+        &mut *store,
+        move |mut caller: Caller<'_, PlugContext<T>>, name_ptr: u32, name_len: u32| -> i32 {
+            read_str(&mut caller, &mem, name_ptr, name_len)
+                .is_some_and(|name| present_deps.contains(&name)) as i32
+        },
+    );
+
+    let mem = memory.clone();
+    let cfg = config.clone();
+    let config_get_str = Func::wrap(
+        &mut *store,
+        move |mut caller: Caller<'_, PlugContext<T>>,
+              key_ptr: u32,
+              key_len: u32,
+              buf_ptr: u32,
+              buf_len: u32|
+              -> wasmtime::Result<i32> {
+            let Some(value) = read_str(&mut caller, &mem, key_ptr, key_len)
+                .and_then(|key| cfg.get(id, &key))
+                .and_then(|value| value.as_str().map(str::to_string))
+            else {
+                return Ok(-1);
+            };
+            let len = value.len().min(buf_len as usize);
+            write_bytes(&mut caller, &mem, buf_ptr, &value.as_bytes()[..len])?;
+            Ok(value.len() as i32)
+        },
+    );
+
+    let mem = memory.clone();
+    let cfg = config.clone();
+    let config_get_int = Func::wrap(
+        &mut *store,
+        move |mut caller: Caller<'_, PlugContext<T>>, key_ptr: u32, key_len: u32, default: i64| {
+            read_str(&mut caller, &mem, key_ptr, key_len)
+                .and_then(|key| cfg.get(id, &key))
+                .and_then(|value| value.as_int())
+                .unwrap_or(default)
+        },
+    );
+
+    let mem = memory.clone();
+    let cfg = config.clone();
+    let config_get_float = Func::wrap(
+        &mut *store,
+        move |mut caller: Caller<'_, PlugContext<T>>, key_ptr: u32, key_len: u32, default: f64| {
+            read_str(&mut caller, &mem, key_ptr, key_len)
+                .and_then(|key| cfg.get(id, &key))
+                .and_then(|value| value.as_float())
+                .unwrap_or(default)
+        },
+    );
+
+    let mem = memory.clone();
+    let config_get_bool = Func::wrap(
+        &mut *store,
+        move |mut caller: Caller<'_, PlugContext<T>>, key_ptr: u32, key_len: u32, default: i32| {
+            read_str(&mut caller, &mem, key_ptr, key_len)
+                .and_then(|key| config.get(id, &key))
+                .and_then(|value| value.as_bool())
+                .map_or(default, |b| b as i32)
+        },
+    );
+
+    let mem = memory.clone();
+    let plug_assets = assets.clone();
+    let asset_size = Func::wrap(
+        &mut *store,
+        move |mut caller: Caller<'_, PlugContext<T>>, name_ptr: u32, name_len: u32| -> i32 {
+            read_str(&mut caller, &mem, name_ptr, name_len)
+                .and_then(|name| Some(plug_assets.get(id)?.get(&name)?.len()))
+                .map_or(-1, |len| len.min(i32::MAX as usize) as i32)
+        },
+    );
+
+    let mem = memory;
+    let asset_read = Func::wrap(
+        &mut *store,
+        move |mut caller: Caller<'_, PlugContext<T>>,
+              name_ptr: u32,
+              name_len: u32,
+              offset: u32,
+              buf_ptr: u32,
+              buf_len: u32|
+              -> wasmtime::Result<i32> {
+            let Some(name) = read_str(&mut caller, &mem, name_ptr, name_len) else {
+                return Ok(-1);
+            };
+            let Some(plug_assets) = assets.get(id) else {
+                return Ok(-1);
+            };
+            let Some(data) = plug_assets.get(&name) else {
+                return Ok(-1);
+            };
+            let start = (offset as usize).min(data.len());
+            let end = start.saturating_add(buf_len as usize).min(data.len());
+            write_bytes(&mut caller, &mem, buf_ptr, &data[start..end])?;
+            Ok((end - start) as i32)
+        },
+    );
+
+    vec![
+        ("has_dep", has_dep),
+        ("config_get_str", config_get_str),
+        ("config_get_int", config_get_int),
+        ("config_get_float", config_get_float),
+        ("config_get_bool", config_get_bool),
+        ("asset_size", asset_size),
+        ("asset_read", asset_read),
+    ]
+}
+
+/// Contents of the assets of a plugin by name
+type Assets = Arc<HashMap<String, Vec<u8>>>;
+
+/// Assets of the plugins that were loaded from archives, shared between [`crate::Plugs`] and the builtin functions
+#[derive(Clone, Default)]
+pub(crate) struct SharedAssets(Arc<RwLock<HashMap<PlugId, Assets>>>);
+
+impl SharedAssets {
+    #[cfg_attr(not(feature = "archive"), allow(dead_code))]
+    pub(crate) fn set(&self, id: PlugId, assets: HashMap<String, Vec<u8>>) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(id, Arc::new(assets));
+    }
+
+    fn get(&self, id: PlugId) -> Option<Assets> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&id)
+            .cloned()
+    }
+
+    pub(crate) fn clear(&self) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+/// Memories of the instantiated plugins, shared between [`crate::Plugs`] and the builtin functions
+///
+/// Builtins that are wrapped to record statistics or run interceptors are called through `Func::call`, which gives them a
+/// `Caller` without the plugin's exports, so they fall back to the memory that was recorded here.
+#[derive(Clone, Default)]
+pub(crate) struct SharedMemories(Arc<RwLock<HashMap<PlugId, Memory>>>);
+
+impl SharedMemories {
+    pub(crate) fn set(&self, id: PlugId, memory: Memory) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(id, memory);
+    }
+
+    pub(crate) fn remove(&self, id: PlugId) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&id);
+    }
+
+    fn get(&self, id: PlugId) -> Option<Memory> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&id)
+            .copied()
+    }
+
+    pub(crate) fn clear(&self) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+/// The `memory` export of the plugin that the builtins were created for
+#[derive(Clone)]
+struct PlugMemory {
+    id: PlugId,
+    memories: SharedMemories,
+}
+
+impl PlugMemory {
+    /// Returns the caller's `memory` export, or the recorded memory of the plugin if the caller has no exports
+    fn get<T>(&self, caller: &mut Caller<'_, PlugContext<T>>) -> Option<Memory> {
+        caller
+            .get_export("memory")
+            .and_then(|e| e.into_memory())
+            .or_else(|| self.memories.get(self.id))
+    }
+}
+
+/// Write bytes to the plugin's `memory` export
+fn write_bytes<T>(
+    caller: &mut Caller<'_, PlugContext<T>>,
+    memory: &PlugMemory,
+    ptr: u32,
+    bytes: &[u8],
+) -> wasmtime::Result<()> {
+    let memory = memory
+        .get(caller)
+        .ok_or_else(|| wasmtime::Error::msg("Plugin has no memory export"))?;
+    memory.write(caller, ptr as usize, bytes)?;
+    Ok(())
+}
+
+/// Read a UTF-8 string from the plugin's `memory` export, returns `None` if the memory or the string is invalid
+fn read_str<T>(
+    caller: &mut Caller<'_, PlugContext<T>>,
+    memory: &PlugMemory,
+    ptr: u32,
+    len: u32,
+) -> Option<String> {
+    let memory = memory.get(caller)?;
+    let start = ptr as usize;
+    let end = start.checked_add(len as usize)?;
+    let bytes = memory.data(&caller).get(start..end)?;
+    String::from_utf8(bytes.to_vec()).ok()
+}
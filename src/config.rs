@@ -0,0 +1,138 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, PoisonError, RwLock},
+};
+
+use crate::PlugId;
+
+/// A configuration value that the host passes to a plugin with [`crate::Plugs::set_config`]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "manifest", derive(serde::Deserialize), serde(untagged))]
+pub enum ConfigValue {
+    Str(String),
+    Int(i64),
+    Float(f64),
+    Bool(bool),
+}
+
+impl ConfigValue {
+    /// Returns the value if it is a string
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ConfigValue::Str(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is an integer
+    pub fn as_int(&self) -> Option<i64> {
+        match self {
+            ConfigValue::Int(i) => Some(*i),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a float or an integer
+    pub fn as_float(&self) -> Option<f64> {
+        match self {
+            ConfigValue::Float(f) => Some(*f),
+            ConfigValue::Int(i) => Some(*i as f64),
+            _ => None,
+        }
+    }
+
+    /// Returns the value if it is a bool
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ConfigValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ConfigValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ConfigValue::Str(s) => write!(f, "{s:?}"),
+            ConfigValue::Int(i) => write!(f, "{i}"),
+            ConfigValue::Float(x) => write!(f, "{x}"),
+            ConfigValue::Bool(b) => write!(f, "{b}"),
+        }
+    }
+}
+
+impl From<&str> for ConfigValue {
+    fn from(value: &str) -> Self {
+        ConfigValue::Str(value.to_string())
+    }
+}
+
+impl From<String> for ConfigValue {
+    fn from(value: String) -> Self {
+        ConfigValue::Str(value)
+    }
+}
+
+impl From<i32> for ConfigValue {
+    fn from(value: i32) -> Self {
+        ConfigValue::Int(value.into())
+    }
+}
+
+impl From<i64> for ConfigValue {
+    fn from(value: i64) -> Self {
+        ConfigValue::Int(value)
+    }
+}
+
+impl From<f64> for ConfigValue {
+    fn from(value: f64) -> Self {
+        ConfigValue::Float(value)
+    }
+}
+
+impl From<bool> for ConfigValue {
+    fn from(value: bool) -> Self {
+        ConfigValue::Bool(value)
+    }
+}
+
+/// Configuration of every plugin, shared between [`crate::Plugs`] and the builtin functions
+#[derive(Clone, Default)]
+pub(crate) struct SharedConfig(Arc<RwLock<HashMap<PlugId, HashMap<String, ConfigValue>>>>);
+
+impl SharedConfig {
+    pub(crate) fn set(&self, id: PlugId, config: HashMap<String, ConfigValue>) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(id, config);
+    }
+
+    /// Add `config` to the configuration of a plugin, replacing existing values of the same keys
+    #[cfg_attr(not(feature = "manifest"), allow(dead_code))]
+    pub(crate) fn merge(&self, id: PlugId, config: HashMap<String, ConfigValue>) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .entry(id)
+            .or_default()
+            .extend(config);
+    }
+
+    pub(crate) fn get(&self, id: PlugId, key: &str) -> Option<ConfigValue> {
+        self.0
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&id)?
+            .get(key)
+            .cloned()
+    }
+
+    pub(crate) fn clear(&self) {
+        self.0
+            .write()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
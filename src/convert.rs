@@ -0,0 +1,232 @@
+use std::fmt::Display;
+
+use wasmtime::{Val, ValType};
+
+use crate::{ArgumentError, ExportNotFound, ExportType, Plugs, UnknownPlugin};
+
+/// Convert a textual argument to a value of type `ty`. Integers may also be written as their unsigned counterpart
+/// (e.g. `4294967295` for an `i32` of -1) and `v128`s as decimal or `0x` prefixed hexadecimal numbers.
+fn parse_val(ty: &ValType, arg: &str) -> Option<Val> {
+    let arg = arg.trim();
+    match ty {
+        ValType::I32 => arg
+            .parse::<i32>()
+            .ok()
+            .or_else(|| arg.parse::<u32>().ok().map(|n| n as i32))
+            .map(Val::I32),
+        ValType::I64 => arg
+            .parse::<i64>()
+            .ok()
+            .or_else(|| arg.parse::<u64>().ok().map(|n| n as i64))
+            .map(Val::I64),
+        ValType::F32 => arg.parse::<f32>().ok().map(Val::from),
+        ValType::F64 => arg.parse::<f64>().ok().map(Val::from),
+        ValType::V128 => match arg.strip_prefix("0x") {
+            Some(hex) => u128::from_str_radix(hex, 16).ok(),
+            None => arg.parse::<u128>().ok(),
+        }
+        .map(|n| Val::V128(n.into())),
+        ValType::Ref(_) => None,
+    }
+}
+
+/// Format a value the way [`parse_val`] reads it
+fn fmt_val(val: &Val) -> Option<String> {
+    match val {
+        Val::I32(n) => Some(n.to_string()),
+        Val::I64(n) => Some(n.to_string()),
+        Val::F32(bits) => Some(f32::from_bits(*bits).to_string()),
+        Val::F64(bits) => Some(f64::from_bits(*bits).to_string()),
+        Val::V128(n) => Some(format!("{:#x}", n.as_u128())),
+        _ => None,
+    }
+}
+
+/// Convert a JSON argument to a value of type `ty`, strings are converted with [`parse_val`]
+#[cfg(feature = "json")]
+fn json_to_val(ty: &ValType, arg: &serde_json::Value) -> Option<Val> {
+    let n = match arg {
+        serde_json::Value::String(s) => return parse_val(ty, s),
+        serde_json::Value::Number(n) => n,
+        _ => return None,
+    };
+    match ty {
+        ValType::I32 => json_int(n)
+            .and_then(|n| {
+                i32::try_from(n)
+                    .ok()
+                    .or_else(|| u32::try_from(n).ok().map(|n| n as i32))
+            })
+            .map(Val::I32),
+        ValType::I64 => json_int(n)
+            .and_then(|n| {
+                i64::try_from(n)
+                    .ok()
+                    .or_else(|| u64::try_from(n).ok().map(|n| n as i64))
+            })
+            .map(Val::I64),
+        ValType::F32 => n.as_f64().map(|n| Val::from(n as f32)),
+        ValType::F64 => n.as_f64().map(Val::from),
+        ValType::V128 => n.as_u64().map(|n| Val::V128(u128::from(n).into())),
+        ValType::Ref(_) => None,
+    }
+}
+
+/// Returns the value of a JSON integer or of a float without a fractional part (e.g. `3.0`)
+#[cfg(feature = "json")]
+fn json_int(n: &serde_json::Number) -> Option<i128> {
+    if let Some(n) = n.as_i64() {
+        return Some(n.into());
+    }
+    if let Some(n) = n.as_u64() {
+        return Some(n.into());
+    }
+    // Infinities and NaN have no whole value, larger floats are out of range for every integer type anyway
+    let x = n.as_f64()?;
+    (x.fract() == 0.0 && x.abs() <= u64::MAX as f64).then_some(x as i128)
+}
+
+/// Convert a value to JSON. Floats that JSON can't represent (NaN and infinities) and `v128`s are returned as strings.
+#[cfg(feature = "json")]
+fn val_to_json(val: &Val) -> Option<serde_json::Value> {
+    let float = |x: f64, text: String| {
+        serde_json::Number::from_f64(x)
+            .map_or(serde_json::Value::String(text), serde_json::Value::Number)
+    };
+    match val {
+        Val::I32(n) => Some((*n).into()),
+        Val::I64(n) => Some((*n).into()),
+        Val::F32(bits) => {
+            // Going through the shortest textual form keeps e.g. 0.1 from becoming 0.10000000149011612
+            let text = f32::from_bits(*bits).to_string();
+            Some(float(text.parse().unwrap_or(f64::NAN), text))
+        }
+        Val::F64(bits) => {
+            let x = f64::from_bits(*bits);
+            Some(float(x, x.to_string()))
+        }
+        Val::V128(n) => Some(serde_json::Value::String(format!("{:#x}", n.as_u128()))),
+        _ => None,
+    }
+}
+
+impl<'a, T> Plugs<'a, T> {
+    /// Call a function of a plugin with arguments in their textual form, e.g. from a debug console.
+    ///
+    /// The arguments are converted according to the function's signature (see [`Plugs::call_dynamic`]): integers can be
+    /// passed as signed or unsigned numbers, floats as any number that Rust can parse and `v128`s as decimal or `0x`
+    /// prefixed hexadecimal numbers. Results are returned in the same format.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`UnknownPlugin::Name`] if the specified plugin couldn't be found.
+    /// - Returns [`ExportNotFound`] if the plugin doesn't export a function with that name.
+    /// - Returns [`ArgumentError`] if the number of arguments is wrong, an argument couldn't be converted or the function
+    ///   has reference type parameters or results.
+    /// - May return any error [`Plugs::call_dynamic`] returns.
+    pub fn call_str(
+        &mut self,
+        plug: &str,
+        func: &str,
+        args: &[&str],
+    ) -> wasmtime::Result<Vec<String>> {
+        self.call_converted(plug, func, args, |ty, arg| parse_val(ty, arg), fmt_val)
+    }
+
+    /// Call a function of a plugin with JSON arguments (see [`Plugs::call_str`])
+    ///
+    /// `args` is an array of arguments, `null` for no arguments or a single argument. Arguments can be numbers or
+    /// strings that [`Plugs::call_str`] accepts. The results are returned as an array, floats that JSON can't represent
+    /// and `v128`s are returned as strings.
+    ///
+    /// # Errors
+    ///
+    /// - May return any error [`Plugs::call_str`] returns.
+    #[cfg(feature = "json")]
+    pub fn call_json(
+        &mut self,
+        plug: &str,
+        func: &str,
+        args: serde_json::Value,
+    ) -> wasmtime::Result<serde_json::Value> {
+        let args = match args {
+            serde_json::Value::Array(args) => args,
+            serde_json::Value::Null => Vec::new(),
+            arg => vec![arg],
+        };
+        let results = self.call_converted(plug, func, &args, json_to_val, val_to_json)?;
+        Ok(serde_json::Value::Array(results))
+    }
+
+    /// Convert `args` according to the signature of `func`, call it with `call_dynamic` and convert its results
+    fn call_converted<A: Display, R>(
+        &mut self,
+        plug: &str,
+        func: &str,
+        args: &[A],
+        to_val: impl Fn(&ValType, &A) -> Option<Val>,
+        from_val: impl Fn(&Val) -> Option<R>,
+    ) -> wasmtime::Result<Vec<R>> {
+        let p = self
+            .get_plug(plug)
+            .ok_or_else(|| UnknownPlugin::Name(plug.to_string()))?;
+        let ty = p
+            .module
+            .get_export(func)
+            .and_then(|ty| ty.func().cloned())
+            .ok_or_else(|| ExportNotFound {
+                export_name: func.to_string(),
+                plug_name: plug.to_string(),
+                expected_ty: ExportType::Func,
+            })?;
+
+        let unsupported = |ty: ValType| {
+            wasmtime::Error::from(ArgumentError::Unsupported {
+                plug_name: plug.to_string(),
+                func: func.to_string(),
+                ty,
+            })
+        };
+        // Checked before the call so that functions whose results can't be converted aren't called at all
+        if let Some(ty) = ty
+            .params()
+            .chain(ty.results())
+            .find(|ty| matches!(ty, ValType::Ref(_)))
+        {
+            return Err(unsupported(ty));
+        }
+        if ty.params().len() != args.len() {
+            return Err(ArgumentError::Count {
+                plug_name: plug.to_string(),
+                func: func.to_string(),
+                expected: ty.params().len(),
+                found: args.len(),
+            }
+            .into());
+        }
+
+        let args = ty
+            .params()
+            .zip(args)
+            .enumerate()
+            .map(|(index, (ty, arg))| {
+                to_val(&ty, arg).ok_or_else(|| {
+                    wasmtime::Error::from(ArgumentError::Invalid {
+                        plug_name: plug.to_string(),
+                        func: func.to_string(),
+                        index,
+                        ty,
+                        value: arg.to_string(),
+                    })
+                })
+            })
+            .collect::<wasmtime::Result<Vec<_>>>()?;
+
+        let results = self.call_dynamic(plug, func, &args)?;
+        results
+            .iter()
+            .zip(ty.results())
+            .map(|(val, ty)| from_val(val).ok_or_else(|| unsupported(ty)))
+            .collect()
+    }
+}
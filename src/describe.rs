@@ -0,0 +1,205 @@
+use wasmtime::{ExternType, Mutability, ValType};
+
+use crate::Plugs;
+
+/// The type of a function parameter or result, global or table element
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum ValueType {
+    I32,
+    I64,
+    F32,
+    F64,
+    V128,
+    /// A reference type in the WebAssembly text format, e.g. `(ref null func)`
+    Ref(String),
+}
+
+impl From<&ValType> for ValueType {
+    fn from(ty: &ValType) -> Self {
+        match ty {
+            ValType::I32 => ValueType::I32,
+            ValType::I64 => ValueType::I64,
+            ValType::F32 => ValueType::F32,
+            ValType::F64 => ValueType::F64,
+            ValType::V128 => ValueType::V128,
+            ValType::Ref(ty) => ValueType::Ref(ty.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for ValueType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValueType::I32 => write!(f, "i32"),
+            ValueType::I64 => write!(f, "i64"),
+            ValueType::F32 => write!(f, "f32"),
+            ValueType::F64 => write!(f, "f64"),
+            ValueType::V128 => write!(f, "v128"),
+            ValueType::Ref(ty) => write!(f, "{ty}"),
+        }
+    }
+}
+
+/// The kind and full type of an export or import
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(tag = "kind", rename_all = "lowercase")
+)]
+pub enum ItemType {
+    Func {
+        params: Vec<ValueType>,
+        results: Vec<ValueType>,
+    },
+    Global {
+        content: ValueType,
+        mutable: bool,
+    },
+    Table {
+        element: ValueType,
+        minimum: u64,
+        maximum: Option<u64>,
+    },
+    /// Limits are in pages of `page_size` bytes
+    Memory {
+        minimum: u64,
+        maximum: Option<u64>,
+        page_size: u64,
+        memory64: bool,
+        shared: bool,
+    },
+}
+
+impl From<&ExternType> for ItemType {
+    fn from(ty: &ExternType) -> Self {
+        match ty {
+            ExternType::Func(ty) => ItemType::Func {
+                params: ty.params().map(|p| ValueType::from(&p)).collect(),
+                results: ty.results().map(|r| ValueType::from(&r)).collect(),
+            },
+            ExternType::Global(ty) => ItemType::Global {
+                content: ty.content().into(),
+                mutable: ty.mutability() == Mutability::Var,
+            },
+            ExternType::Table(ty) => ItemType::Table {
+                element: ValueType::Ref(ty.element().to_string()),
+                minimum: ty.minimum(),
+                maximum: ty.maximum(),
+            },
+            ExternType::Memory(ty) => ItemType::Memory {
+                minimum: ty.minimum(),
+                maximum: ty.maximum(),
+                page_size: ty.page_size(),
+                memory64: ty.is_64(),
+                shared: ty.is_shared(),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for ItemType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let join = |types: &[ValueType]| {
+            types
+                .iter()
+                .map(|ty| ty.to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        match self {
+            ItemType::Func { params, results } => match results.as_slice() {
+                [] => write!(f, "func({})", join(params)),
+                [result] => write!(f, "func({}) -> {result}", join(params)),
+                _ => write!(f, "func({}) -> ({})", join(params), join(results)),
+            },
+            ItemType::Global {
+                content,
+                mutable: true,
+            } => write!(f, "global mut {content}"),
+            ItemType::Global {
+                content,
+                mutable: false,
+            } => write!(f, "global {content}"),
+            ItemType::Table {
+                element,
+                minimum,
+                maximum,
+            } => match maximum {
+                Some(max) => write!(f, "table {element} [{minimum}, {max}]"),
+                None => write!(f, "table {element} [{minimum}, ..]"),
+            },
+            ItemType::Memory {
+                minimum, maximum, ..
+            } => match maximum {
+                Some(max) => write!(f, "memory [{minimum}, {max}] pages"),
+                None => write!(f, "memory [{minimum}, ..] pages"),
+            },
+        }
+    }
+}
+
+/// An export of a plugin, see [`PlugDescription`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ExportDescription {
+    pub name: String,
+    pub ty: ItemType,
+}
+
+/// An import of a plugin, see [`PlugDescription`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct ImportDescription {
+    pub module: String,
+    pub name: String,
+    pub ty: ItemType,
+}
+
+/// The exports and imports of a plugin together with their types, see [`Plugs::describe`]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct PlugDescription {
+    pub name: String,
+    /// Exports in the order they are declared in the module, including metadata exports and memories
+    pub exports: Vec<ExportDescription>,
+    /// Imports in the order they are declared in the module
+    pub imports: Vec<ImportDescription>,
+}
+
+impl<'a, T> Plugs<'a, T> {
+    /// Returns the kind and type of every export and import of a plugin, `None` if there is no plugin with that name.
+    ///
+    /// With the `serde` feature the description can be serialized, e.g. to pass it on to external tooling.
+    pub fn describe(&self, name: &str) -> Option<PlugDescription> {
+        let plug = self.get_plug(name)?;
+        let exports = plug
+            .module
+            .exports()
+            .map(|export| ExportDescription {
+                name: export.name().to_string(),
+                ty: ItemType::from(&export.ty()),
+            })
+            .collect();
+        let imports = plug
+            .imports
+            .iter()
+            .map(|imp| ImportDescription {
+                module: imp.module.clone(),
+                name: imp.name.clone(),
+                ty: ItemType::from(&imp.ty),
+            })
+            .collect();
+
+        Some(PlugDescription {
+            name: plug.name.clone(),
+            exports,
+            imports,
+        })
+    }
+}
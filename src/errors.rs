@@ -1,6 +1,6 @@
-use wasmtime::{ExternType, ValType};
+use wasmtime::{ExternType, FuncType, Trap, ValType};
 
-use crate::PlugId;
+use crate::{PlugId, PlugImport, PlugStatus, MAX_METADATA_LEN};
 
 #[derive(Clone, Debug)]
 /// "Plugin with name '{name}' already exists"
@@ -22,6 +22,120 @@ impl std::fmt::Display for PluginAlreadyExists {
 
 impl core::error::Error for PluginAlreadyExists {}
 
+#[derive(Clone, Debug)]
+/// "Plugin '{name}' is disabled"
+pub struct PluginDisabled {
+    pub(crate) name: String,
+}
+
+impl PluginDisabled {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl std::fmt::Display for PluginDisabled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Plugin '{}' is disabled", self.name)
+    }
+}
+
+impl core::error::Error for PluginDisabled {}
+
+#[derive(Clone, Debug)]
+/// "Plugin '{name}' is {status} but it needs to be {expected}"
+pub struct InvalidPlugStatus {
+    pub(crate) name: String,
+    pub(crate) status: PlugStatus,
+    pub(crate) expected: PlugStatus,
+}
+
+impl InvalidPlugStatus {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn status(&self) -> &PlugStatus {
+        &self.status
+    }
+
+    pub fn expected(&self) -> &PlugStatus {
+        &self.expected
+    }
+}
+
+impl std::fmt::Display for InvalidPlugStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Plugin '{}' is {} but it needs to be {}",
+            self.name, self.status, self.expected
+        )
+    }
+}
+
+impl core::error::Error for InvalidPlugStatus {}
+
+#[derive(Clone, Debug)]
+/// "Plugin '{name}' depends on '{dep}', which failed"
+pub struct DependencyFailed {
+    pub(crate) name: String,
+    pub(crate) dep: String,
+}
+
+impl DependencyFailed {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Name of the dependency whose status is [`PlugStatus::Failed`]
+    pub fn dep(&self) -> &str {
+        &self.dep
+    }
+}
+
+impl std::fmt::Display for DependencyFailed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Plugin '{}' depends on '{}', which failed",
+            self.name, self.dep
+        )
+    }
+}
+
+impl core::error::Error for DependencyFailed {}
+
+/// Errors of [`crate::Plugs::enable`] and [`crate::Plugs::disable`]
+#[derive(Clone, Debug)]
+pub enum ToggleError {
+    /// "Plugin '{name}' can't be disabled because {dependents:?} depend on it"
+    HasDependents {
+        name: String,
+        dependents: Vec<String>,
+    },
+
+    /// "Plugin '{name}' can't be enabled because its dependency '{dep}' is disabled"
+    DependencyDisabled { name: String, dep: String },
+}
+
+impl std::fmt::Display for ToggleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ToggleError::HasDependents { name, dependents } => write!(
+                f,
+                "Plugin '{name}' can't be disabled because {dependents:?} depend on it"
+            ),
+            ToggleError::DependencyDisabled { name, dep } => write!(
+                f,
+                "Plugin '{name}' can't be enabled because its dependency '{dep}' is disabled"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for ToggleError {}
+
 #[derive(Clone, Debug)]
 pub enum UnknownPlugin {
     /// "Plugin with id '{id}' couldn't be found"
@@ -44,27 +158,50 @@ impl std::fmt::Display for UnknownPlugin {
 
 impl core::error::Error for UnknownPlugin {}
 
-#[derive(Clone, Debug)]
+/// The kind of a plugin's export or import
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "lowercase")
+)]
 pub enum ExportType {
     Memory,
+    /// Serialized as `"function"` like its `Display` output
+    #[cfg_attr(feature = "serde", serde(rename = "function"))]
     Func,
+    Global,
+    Table,
 }
 
-impl From<ExternType> for ExportType {
-    fn from(value: ExternType) -> Self {
+impl From<&ExternType> for ExportType {
+    fn from(value: &ExternType) -> Self {
         match value {
             ExternType::Func(_) => ExportType::Func,
-            ExternType::Global(_) => {
-                panic!("wlug::ExportType can't be created from wasmtime::ExternType::Global")
-            }
-            ExternType::Table(_) => {
-                panic!("wlug::ExportType can't be created from wasmtime::ExternType::Table")
-            }
+            ExternType::Global(_) => ExportType::Global,
+            ExternType::Table(_) => ExportType::Table,
             ExternType::Memory(_) => ExportType::Memory,
         }
     }
 }
 
+impl From<ExternType> for ExportType {
+    fn from(value: ExternType) -> Self {
+        ExportType::from(&value)
+    }
+}
+
+impl std::fmt::Display for ExportType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExportType::Memory => write!(f, "memory"),
+            ExportType::Func => write!(f, "function"),
+            ExportType::Global => write!(f, "global"),
+            ExportType::Table => write!(f, "table"),
+        }
+    }
+}
+
 #[derive(Debug)]
 /// "Export '{export_name}' not found in plugin '{plug_name}'",
 /// The `expected_ty` field stores the expected type of the export which was not found.
@@ -84,7 +221,7 @@ impl ExportNotFound {
     }
 
     pub fn expected_ty(&self) -> ExportType {
-        self.expected_ty.clone()
+        self.expected_ty
     }
 }
 
@@ -101,6 +238,46 @@ impl std::fmt::Display for ExportNotFound {
 impl core::error::Error for ExportNotFound {}
 
 #[derive(Clone, Debug)]
+/// Errors that are returned when a metadata export (e.g. `__name` or `__deps`) returned an invalid string
+pub enum MetadataError {
+    /// "Metadata export '{export}' returned the pointer {ptr} which is outside of the plugin's memory"
+    OutOfBounds { export: String, ptr: u32 },
+    /// "String returned by metadata export '{export}' isn't NUL-terminated within {MAX_METADATA_LEN} bytes"
+    Unterminated { export: String },
+    /// "String returned by metadata export '{export}' isn't valid UTF-8"
+    InvalidUtf8 { export: String },
+    /// "Metadata export '{export}' returned the invalid plugin name '{name}'", plugin names must not be empty, contain
+    /// ';' or start with '?'
+    InvalidName { export: String, name: String },
+}
+
+impl std::fmt::Display for MetadataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetadataError::OutOfBounds { export, ptr } => write!(
+                f,
+                "Metadata export '{export}' returned the pointer {ptr} which is outside of the plugin's memory"
+            ),
+            MetadataError::Unterminated { export } => write!(
+                f,
+                "String returned by metadata export '{export}' isn't NUL-terminated within {MAX_METADATA_LEN} bytes"
+            ),
+            MetadataError::InvalidUtf8 { export } => write!(
+                f,
+                "String returned by metadata export '{export}' isn't valid UTF-8"
+            ),
+            MetadataError::InvalidName { export, name } => write!(
+                f,
+                "Metadata export '{export}' returned the invalid plugin name '{name}'"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for MetadataError {}
+
+#[derive(Clone, Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum LinkError {
     /// "Dependency '{dep_name}' in plugin '{plug_name}' hasn't been instantiated yet"
     NotInstantiated { dep_name: String, plug_name: String },
@@ -118,8 +295,34 @@ pub enum LinkError {
     /// "Plugin '{plug_name}' has unresolved imports: {unresolved_imports:?}"
     UnresolvedImports {
         plug_name: String,
-        unresolved_imports: Vec<String>,
+        unresolved_imports: Vec<PlugImport>,
     },
+
+    /// "Import '{import}' of plugin '{plug}' expects {expected} but '{provider}' provides {found}"
+    ///
+    /// `provider` is either the name of the dependency that provides the import or [`crate::HOST_PROVIDER`].
+    SignatureMismatch {
+        plug: String,
+        import: PlugImport,
+        provider: String,
+        expected: FuncType,
+        found: FuncType,
+    },
+
+    /// "Import '{import}' of plugin '{plug}' expects a {expected} but '{provider}' provides a {found}"
+    ///
+    /// `provider` is either the name of the dependency that provides the import or [`crate::HOST_PROVIDER`].
+    KindMismatch {
+        plug: String,
+        import: PlugImport,
+        provider: String,
+        expected: ExportType,
+        found: ExportType,
+    },
+
+    /// "Plugin '{plug}' imports host function '{import}' which isn't in its capabilities", see
+    /// [`crate::Plug::capabilities`]
+    CapabilityNotGranted { plug: String, import: PlugImport },
 }
 
 impl std::fmt::Display for LinkError {
@@ -141,8 +344,34 @@ impl std::fmt::Display for LinkError {
             LinkError::UnresolvedImports {
                 plug_name,
                 unresolved_imports,
-            } => write!(f, "Plugin '{plug_name}' has unresolved imports: {unresolved_imports:?}",
-),
+            } => {
+                write!(f, "Plugin '{plug_name}' has unresolved imports: [")?;
+                for (i, imp) in unresolved_imports.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "\"{imp}\"")?;
+                }
+                write!(f, "]")
+            }
+            LinkError::SignatureMismatch {
+                plug,
+                import,
+                provider,
+                expected,
+                found,
+            } => write!(f, "Import '{import}' of plugin '{plug}' expects {expected} but '{provider}' provides {found}"),
+            LinkError::KindMismatch {
+                plug,
+                import,
+                provider,
+                expected,
+                found,
+            } => write!(f, "Import '{import}' of plugin '{plug}' expects a {expected} but '{provider}' provides a {found}"),
+            LinkError::CapabilityNotGranted { plug, import } => write!(
+                f,
+                "Plugin '{plug}' imports host function '{import}' which isn't in its capabilities"
+            ),
         }
     }
 }
@@ -183,3 +412,381 @@ impl std::fmt::Display for TypeMismatchError {
 }
 
 impl core::error::Error for TypeMismatchError {}
+
+#[derive(Clone, Debug)]
+/// Errors that are returned when the arguments or results of [`crate::Plugs::call_str`] or `call_json` couldn't be
+/// converted
+pub enum ArgumentError {
+    /// "Function '{func}' in plugin '{plug_name}' expects {expected} argument(s) but got {found}"
+    Count {
+        plug_name: String,
+        func: String,
+        expected: usize,
+        found: usize,
+    },
+    /// "Couldn't convert argument {index} ('{value}') of function '{func}' in plugin '{plug_name}' to {ty}"
+    Invalid {
+        plug_name: String,
+        func: String,
+        index: usize,
+        ty: ValType,
+        value: String,
+    },
+    /// "Function '{func}' in plugin '{plug_name}' has a parameter or result of type {ty} which can't be converted",
+    /// reference types can't be converted
+    Unsupported {
+        plug_name: String,
+        func: String,
+        ty: ValType,
+    },
+}
+
+impl std::fmt::Display for ArgumentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArgumentError::Count {
+                plug_name,
+                func,
+                expected,
+                found,
+            } => write!(
+                f,
+                "Function '{func}' in plugin '{plug_name}' expects {expected} argument(s) but got {found}"
+            ),
+            ArgumentError::Invalid {
+                plug_name,
+                func,
+                index,
+                ty,
+                value,
+            } => write!(
+                f,
+                "Couldn't convert argument {index} ('{value}') of function '{func}' in plugin '{plug_name}' to {ty}"
+            ),
+            ArgumentError::Unsupported {
+                plug_name,
+                func,
+                ty,
+            } => write!(
+                f,
+                "Function '{func}' in plugin '{plug_name}' has a parameter or result of type {ty} which can't be converted"
+            ),
+        }
+    }
+}
+
+impl core::error::Error for ArgumentError {}
+
+/// "Init export of plugin '{plug_name}' failed: {error}"
+#[derive(Debug)]
+pub struct InitError {
+    pub(crate) plug_name: String,
+    pub(crate) error: wasmtime::Error,
+}
+
+impl InitError {
+    pub fn plug_name(&self) -> &str {
+        &self.plug_name
+    }
+
+    /// The error returned by the init export, e.g. a [`PlugTrap`] or [`PluginDisabled`]
+    pub fn error(&self) -> &wasmtime::Error {
+        &self.error
+    }
+}
+
+impl std::fmt::Display for InitError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Init export of plugin '{}' failed: {}",
+            self.plug_name, self.error
+        )
+    }
+}
+
+impl core::error::Error for InitError {
+    fn source(&self) -> Option<&(dyn core::error::Error + 'static)> {
+        Some(self.error.as_ref())
+    }
+}
+
+/// A frame in the backtrace of a [`PlugTrap`]
+#[derive(Clone, Debug)]
+pub struct TrapFrame {
+    /// Name of the plugin the function belongs to, `None` if the module isn't a loaded plugin
+    pub plug: Option<String>,
+    pub func_index: u32,
+    pub func_name: Option<String>,
+    /// Offset of the instruction in the plugin's module
+    pub module_offset: Option<usize>,
+}
+
+impl std::fmt::Display for TrapFrame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}!", self.plug.as_deref().unwrap_or("<unknown>"))?;
+        match &self.func_name {
+            Some(name) => write!(f, "{name}")?,
+            None => write!(f, "<wasm function {}>", self.func_index)?,
+        }
+        if let Some(offset) = self.module_offset {
+            write!(f, " @ {offset:#x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// "Plugin '{plug_name}' trapped in '{func_name}': {trap}", followed by the backtrace.
+///
+/// A call into a plugin made through `call`, `call_dynamic` or `init` trapped, or it failed because a host function
+/// returned an error (`trap` is `None` in that case). `PlugTrap` is attached as context to the original error, so
+/// [`wasmtime::Trap`], [`wasmtime::WasmBacktrace`] and errors returned by host functions can still be downcast from it.
+#[derive(Clone, Debug)]
+pub struct PlugTrap {
+    pub(crate) plug_name: String,
+    pub(crate) func_name: String,
+    pub(crate) trap: Option<Trap>,
+    pub(crate) frames: Vec<TrapFrame>,
+}
+
+impl PlugTrap {
+    /// Name of the plugin that was called by the host
+    pub fn plug_name(&self) -> &str {
+        &self.plug_name
+    }
+
+    /// Name of the function that was called by the host
+    pub fn func_name(&self) -> &str {
+        &self.func_name
+    }
+
+    pub fn trap(&self) -> Option<Trap> {
+        self.trap
+    }
+
+    /// Frames of the wasm backtrace, innermost first. Empty if backtraces are disabled in the engine's config.
+    pub fn frames(&self) -> &[TrapFrame] {
+        &self.frames
+    }
+}
+
+impl std::fmt::Display for PlugTrap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.trap {
+            Some(trap) => write!(
+                f,
+                "Plugin '{}' trapped in '{}': {trap}",
+                self.plug_name, self.func_name
+            )?,
+            None => write!(
+                f,
+                "Call to '{}' in plugin '{}' failed in a host function",
+                self.func_name, self.plug_name
+            )?,
+        }
+        if !self.frames.is_empty() {
+            write!(f, "\nbacktrace:")?;
+            for (i, frame) in self.frames.iter().enumerate() {
+                write!(f, "\n    {i}: {frame}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl core::error::Error for PlugTrap {}
+
+/// A plugin in the WebAssembly text format couldn't be parsed. `line` and `column` are 1-based, `path` is `None` if the
+/// text wasn't loaded from a file.
+#[cfg(feature = "wat")]
+#[derive(Clone, Debug)]
+pub struct WatError {
+    pub(crate) path: Option<std::path::PathBuf>,
+    pub(crate) line: usize,
+    pub(crate) column: usize,
+    pub(crate) message: String,
+}
+
+#[cfg(feature = "wat")]
+impl WatError {
+    pub fn path(&self) -> Option<&std::path::Path> {
+        self.path.as_deref()
+    }
+
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+#[cfg(feature = "wat")]
+impl std::fmt::Display for WatError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = self
+            .path
+            .as_ref()
+            .map_or("<wat>".into(), |path| path.to_string_lossy());
+        write!(
+            f,
+            "Couldn't parse WebAssembly text at {path}:{}:{}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+#[cfg(feature = "wat")]
+impl core::error::Error for WatError {}
+
+#[cfg(feature = "manifest")]
+#[derive(Clone, Debug)]
+/// "Couldn't parse manifest '{path}': {message}"
+pub struct ManifestError {
+    pub(crate) path: std::path::PathBuf,
+    pub(crate) message: String,
+}
+
+#[cfg(feature = "manifest")]
+impl ManifestError {
+    pub fn path(&self) -> &std::path::Path {
+        &self.path
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+#[cfg(feature = "manifest")]
+impl std::fmt::Display for ManifestError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Couldn't parse manifest '{}': {}",
+            self.path.to_string_lossy(),
+            self.message
+        )
+    }
+}
+
+#[cfg(feature = "manifest")]
+impl core::error::Error for ManifestError {}
+
+#[cfg(feature = "verify")]
+#[derive(Clone, Debug)]
+/// Errors that are returned when a plugin binary couldn't be verified, see [`crate::Verification`]
+pub enum VerificationError {
+    /// "Expected SHA-256 digest {expected} but found {found}"
+    DigestMismatch { expected: [u8; 32], found: [u8; 32] },
+    /// "Plugin isn't signed"
+    MissingSignature,
+    /// "Plugin signature isn't 64 bytes long"
+    MalformedSignature,
+    /// "Plugin isn't signed by a trusted key"
+    UntrustedSignature,
+    /// "Plugin isn't a valid WebAssembly binary"
+    MalformedModule,
+}
+
+#[cfg(feature = "verify")]
+impl std::fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let hex = |bytes: &[u8]| bytes.iter().map(|b| format!("{b:02x}")).collect::<String>();
+        match self {
+            VerificationError::DigestMismatch { expected, found } => write!(
+                f,
+                "Expected SHA-256 digest {} but found {}",
+                hex(expected),
+                hex(found)
+            ),
+            VerificationError::MissingSignature => write!(f, "Plugin isn't signed"),
+            VerificationError::MalformedSignature => {
+                write!(f, "Plugin signature isn't 64 bytes long")
+            }
+            VerificationError::UntrustedSignature => {
+                write!(f, "Plugin isn't signed by a trusted key")
+            }
+            VerificationError::MalformedModule => {
+                write!(f, "Plugin isn't a valid WebAssembly binary")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "verify")]
+impl core::error::Error for VerificationError {}
+
+#[cfg(feature = "archive")]
+#[derive(Clone, Debug)]
+/// Errors that are returned when a `.wlug` archive can't be used
+pub enum ArchiveError {
+    /// "Archive '{path}' doesn't contain '{entry}'"
+    MissingEntry {
+        path: std::path::PathBuf,
+        entry: String,
+    },
+    /// "Entry '{entry}' of archive '{path}' is larger than {limit} bytes", see
+    /// [`crate::MAX_ARCHIVE_ENTRY_SIZE`]
+    EntryTooLarge {
+        path: std::path::PathBuf,
+        entry: String,
+        limit: u64,
+    },
+    /// "Contents of archive '{path}' are larger than {limit} bytes", see [`crate::MAX_ARCHIVE_SIZE`]
+    TooLarge {
+        path: std::path::PathBuf,
+        limit: u64,
+    },
+}
+
+#[cfg(feature = "archive")]
+impl ArchiveError {
+    pub fn path(&self) -> &std::path::Path {
+        match self {
+            ArchiveError::MissingEntry { path, .. }
+            | ArchiveError::EntryTooLarge { path, .. }
+            | ArchiveError::TooLarge { path, .. } => path,
+        }
+    }
+
+    /// The entry the error is about, `None` if it is about the whole archive
+    pub fn entry(&self) -> Option<&str> {
+        match self {
+            ArchiveError::MissingEntry { entry, .. }
+            | ArchiveError::EntryTooLarge { entry, .. } => Some(entry),
+            ArchiveError::TooLarge { .. } => None,
+        }
+    }
+}
+
+#[cfg(feature = "archive")]
+impl std::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ArchiveError::MissingEntry { path, entry } => write!(
+                f,
+                "Archive '{}' doesn't contain '{entry}'",
+                path.to_string_lossy()
+            ),
+            ArchiveError::EntryTooLarge { path, entry, limit } => write!(
+                f,
+                "Entry '{entry}' of archive '{}' is larger than {limit} bytes",
+                path.to_string_lossy()
+            ),
+            ArchiveError::TooLarge { path, limit } => write!(
+                f,
+                "Contents of archive '{}' are larger than {limit} bytes",
+                path.to_string_lossy()
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "archive")]
+impl core::error::Error for ArchiveError {}
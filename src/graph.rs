@@ -0,0 +1,153 @@
+use std::fmt::Write;
+
+use crate::{ExportType, ImportSource, PlugId, Plugs};
+
+/// A plugin in a [`DependencyGraph`]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct GraphNode {
+    pub id: PlugId,
+    pub name: String,
+}
+
+/// An entry of a plugin's `__deps`, `to` is `None` if the dependency isn't loaded
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DepEdge {
+    pub from: PlugId,
+    pub to: Option<PlugId>,
+    pub dep: String,
+    pub optional: bool,
+}
+
+/// An import of plugin `from` that is resolved from an export of plugin `to`
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct SymbolEdge {
+    pub from: PlugId,
+    pub to: PlugId,
+    pub module: String,
+    pub name: String,
+    pub kind: ExportType,
+}
+
+/// Plugins and the dependencies between them, see [`Plugs::dependency_graph`]
+///
+/// With the `serde` feature the graph implements `serde::Serialize`, e.g. to pass it on to external tooling.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DependencyGraph {
+    pub nodes: Vec<GraphNode>,
+    pub deps: Vec<DepEdge>,
+    pub symbols: Vec<SymbolEdge>,
+}
+
+impl DependencyGraph {
+    /// Serialize the graph in the Graphviz DOT format.
+    ///
+    /// Each dependency is an edge labeled with the symbols that are imported through it, optional dependencies are
+    /// dashed and dependencies that aren't loaded are drawn as red nodes.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph plugs {\n");
+        for node in self.nodes.iter() {
+            let _ = writeln!(dot, "    {};", dot_id(&node.name));
+        }
+        for edge in self.deps.iter().filter(|edge| edge.to.is_none()) {
+            let _ = writeln!(dot, "    {} [style=dashed, color=red];", dot_id(&edge.dep));
+        }
+
+        for edge in self.deps.iter() {
+            let from = &self.nodes[edge.from].name;
+            let label = self
+                .symbols
+                .iter()
+                .filter(|sym| sym.from == edge.from && Some(sym.to) == edge.to)
+                .map(|sym| sym.name.as_str())
+                .collect::<Vec<_>>()
+                .join("\n");
+
+            let mut attrs = Vec::new();
+            if !label.is_empty() {
+                attrs.push(format!("label={}", dot_id(&label)));
+            }
+            if edge.optional {
+                attrs.push("style=dashed".to_string());
+            }
+
+            let _ = write!(dot, "    {} -> {}", dot_id(from), dot_id(&edge.dep));
+            if !attrs.is_empty() {
+                let _ = write!(dot, " [{}]", attrs.join(", "));
+            }
+            dot.push_str(";\n");
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Serialize the graph as a JSON object with `nodes`, `deps` and `symbols` arrays that mirror the fields of
+    /// [`DependencyGraph`]. Missing dependencies have a `null` `to` field and symbol kinds are strings such as `"function"`.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self)
+            .expect("dependency graphs only contain types that serialize to JSON")
+    }
+}
+
+impl<T> Plugs<'_, T> {
+    /// Build the dependency graph of the loaded plugins. Symbol edges are resolved the same way as in [`Plugs::link`]
+    /// (see [`Plugs::plan_link`]), imports that can't be resolved are left out.
+    pub fn dependency_graph(&self) -> DependencyGraph {
+        let nodes = self
+            .items
+            .iter()
+            .enumerate()
+            .map(|(id, p)| GraphNode {
+                id,
+                name: p.name.clone(),
+            })
+            .collect();
+
+        let mut deps = Vec::new();
+        for (id, p) in self.items.iter().enumerate() {
+            for dep in p.deps.iter() {
+                deps.push(DepEdge {
+                    from: id,
+                    to: self.get_id(dep),
+                    dep: dep.clone(),
+                    optional: p.optional_deps.contains(dep),
+                });
+            }
+        }
+
+        let mut symbols = Vec::new();
+        for p_plan in self.plan_link().plugs {
+            for resolved in p_plan.imports {
+                if let ImportSource::Dep { id, .. } = resolved.source {
+                    symbols.push(SymbolEdge {
+                        from: p_plan.id,
+                        to: id,
+                        module: resolved.import.module,
+                        name: resolved.import.name,
+                        kind: ExportType::from(resolved.import.ty),
+                    });
+                }
+            }
+        }
+
+        DependencyGraph {
+            nodes,
+            deps,
+            symbols,
+        }
+    }
+}
+
+/// Quote and escape a DOT identifier
+fn dot_id(s: &str) -> String {
+    format!(
+        "\"{}\"",
+        s.replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
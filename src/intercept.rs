@@ -0,0 +1,112 @@
+use std::sync::Arc;
+
+use wasmtime::{Func, Store, Val};
+
+use crate::{PlugContext, PlugId};
+
+/// The function that is called in an intercepted call
+#[derive(Clone, Debug)]
+pub enum Callee {
+    /// An export of a plugin
+    Plug {
+        id: PlugId,
+        name: String,
+        func: String,
+    },
+    /// A host function (including weak defaults and the functions of [`crate::BUILTIN_MODULE`])
+    Host { module: String, name: String },
+}
+
+impl std::fmt::Display for Callee {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Callee::Plug { name, func, .. } => write!(f, "{name}.{func}"),
+            Callee::Host { module, name } => write!(f, "{module}.{name}"),
+        }
+    }
+}
+
+/// A call that is passed to an [`Interceptor`]
+#[derive(Clone, Debug)]
+pub struct CallInfo<'a> {
+    /// The plugin that makes the call, `None` if the host calls a plugin through `call` or `call_dynamic`
+    pub caller: Option<PlugId>,
+    pub callee: &'a Callee,
+    pub args: &'a [Val],
+}
+
+/// Hooks that run around calls made through [`crate::Plugs::call`] and [`crate::Plugs::call_dynamic`], calls to host
+/// functions and calls from one plugin to another, see [`crate::Plugs::add_interceptor`].
+///
+/// `before` hooks run in the order the interceptors were added and `after` hooks run in the reverse order.
+pub trait Interceptor: Send + Sync {
+    /// Called before the callee. Returning `Some(results)` skips the callee and the `before` hooks of the interceptors
+    /// that were added after this one, the call then returns `results` which must match the callee's result types.
+    fn before(&self, call: &CallInfo<'_>) -> Option<Vec<Val>> {
+        let _ = call;
+        None
+    }
+
+    /// Called after the callee returned (or after a `before` hook short-circuited the call) with the results of the call
+    fn after(&self, call: &CallInfo<'_>, results: Result<&[Val], &wasmtime::Error>) {
+        let _ = (call, results);
+    }
+}
+
+/// Run `call` surrounded by the hooks of `interceptors`, `call` is expected to write its results into `results`
+pub(crate) fn intercept(
+    interceptors: &[Arc<dyn Interceptor>],
+    info: &CallInfo<'_>,
+    results: &mut [Val],
+    call: impl FnOnce(&mut [Val]) -> wasmtime::Result<()>,
+) -> wasmtime::Result<()> {
+    let mut ran = 0;
+    let mut short_circuit = None;
+    for interceptor in interceptors {
+        ran += 1;
+        if let Some(vals) = interceptor.before(info) {
+            short_circuit = Some(vals);
+            break;
+        }
+    }
+
+    let res = match short_circuit {
+        Some(vals) if vals.len() != results.len() => Err(wasmtime::Error::msg(format!(
+            "Interceptor of '{}' returned {} result(s) but {} were expected",
+            info.callee,
+            vals.len(),
+            results.len()
+        ))),
+        Some(vals) => {
+            results.clone_from_slice(&vals);
+            Ok(())
+        }
+        None => call(results),
+    };
+
+    for interceptor in interceptors[..ran].iter().rev() {
+        interceptor.after(info, res.as_ref().map(|_| &*results));
+    }
+    res
+}
+
+/// Wrap `func` so that calls to it from the plugin `caller` (or the host if `caller` is `None`) run `interceptors`
+pub(crate) fn wrap_func<T>(
+    store: &mut Store<PlugContext<T>>,
+    interceptors: Vec<Arc<dyn Interceptor>>,
+    caller: Option<PlugId>,
+    callee: Callee,
+    func: Func,
+) -> Func {
+    let ty = func.ty(&*store);
+    Func::new(&mut *store, ty, move |mut store, args, results| {
+        let info = CallInfo {
+            caller,
+            callee: &callee,
+            args,
+        };
+        intercept(&interceptors, &info, results, |results| {
+            func.call(&mut store, args, results)
+        })
+    })
+}
@@ -1,21 +1,68 @@
-use std::{collections::HashMap, path::Path};
+use std::{collections::HashMap, path::Path, sync::Arc, time::Instant};
+#[cfg(feature = "archive")]
+mod archive;
+mod builtins;
+mod config;
+mod convert;
+mod describe;
 mod errors;
+mod graph;
+mod intercept;
+#[cfg(feature = "manifest")]
+mod manifest;
+mod plan;
+mod stats;
+pub mod testing;
+#[cfg(feature = "verify")]
+mod verify;
+#[cfg(feature = "wat")]
+mod wat;
 
 use wasmtime::{
-    Engine, Extern, Func, Instance, IntoFunc, Linker, Module, Store, TypedFunc, WasmParams,
-    WasmResults,
+    Caller, Engine, Extern, ExternType, Func, FuncType, Global, Instance, IntoFunc, Linker, Memory,
+    Module, Ref, Store, Table, Trap, TypedFunc, WasmBacktrace, WasmParams, WasmResults,
 };
 
 // Re-export wasmtime
 pub use wasmtime;
 pub use wasmtime::{Val, ValType};
 
+#[cfg(feature = "archive")]
+pub use archive::{
+    ArchiveManifest, PlugArchive, ARCHIVE_ASSETS_DIR, ARCHIVE_MANIFEST, MAX_ARCHIVE_ENTRY_SIZE,
+    MAX_ARCHIVE_SIZE,
+};
+use builtins::{SharedAssets, SharedMemories};
+pub use builtins::{BUILTIN_FNS, BUILTIN_MODULE};
+pub use config::ConfigValue;
+use config::SharedConfig;
+pub use describe::{ExportDescription, ImportDescription, ItemType, PlugDescription, ValueType};
 pub use errors::*;
+pub use graph::*;
+pub use intercept::{CallInfo, Callee, Interceptor};
+#[cfg(feature = "manifest")]
+pub use manifest::*;
+pub use plan::*;
+use stats::StatsRecorder;
+pub use stats::{CallStats, PlugStats, Stats};
+#[cfg(feature = "verify")]
+pub use verify::{embed_signature, sha256, strip_signature, Verification, SIGNATURE_SECTION};
 
 pub const DEFAULT_DEPS_EXPORT: &str = "__deps";
 pub const DEFAULT_INIT_EXPORT: &str = "__init";
 pub const DEFAULT_RESET_EXPORT: &str = "__reset";
 pub const DEFAULT_NAME_EXPORT: &str = "__name";
+pub const DEFAULT_VERSION_EXPORT: &str = "__version";
+pub const DEFAULT_DESCRIPTION_EXPORT: &str = "__description";
+pub const DEFAULT_AUTHORS_EXPORT: &str = "__authors";
+pub const DEFAULT_LICENSE_EXPORT: &str = "__license";
+pub const DEFAULT_HOMEPAGE_EXPORT: &str = "__homepage";
+
+/// Maximum length in bytes of the strings returned by metadata exports, not including the NUL terminator
+pub const MAX_METADATA_LEN: usize = 4096;
+
+/// Name used in place of a plugin name when an import is provided by a host function (see [`LinkError::SignatureMismatch`])
+pub const HOST_PROVIDER: &str = "<host>";
 
 pub type PlugId = usize;
 
@@ -27,15 +74,141 @@ pub struct Plug<T> {
     pub linker: Linker<PlugContext<T>>,
     pub instance: Option<Instance>,
     pub deps: Vec<String>,
+    pub optional_deps: Vec<String>,
     pub exports: Vec<String>,
-    pub imports: Vec<String>,
+    pub imports: Vec<PlugImport>,
+    /// Version returned by the plugin's version export, `None` if it doesn't have one
+    pub version: Option<String>,
+    /// Description returned by the plugin's description export, `None` if it doesn't have one
+    pub description: Option<String>,
+    /// Authors returned by the plugin's authors export, empty if it doesn't have one
+    pub authors: Vec<String>,
+    /// License returned by the plugin's license export, `None` if it doesn't have one
+    pub license: Option<String>,
+    /// Homepage returned by the plugin's homepage export, `None` if it doesn't have one
+    pub homepage: Option<String>,
+    /// Fuel available to each call into the plugin made through `call`, `call_dynamic` and `init`. Calls into the plugin
+    /// return an error if this is set but fuel consumption isn't enabled in the engine's config.
+    pub fuel_limit: Option<u64>,
+    /// Host functions the plugin is allowed to import, either whole modules (`"env"`) or single functions
+    /// (`"env.print"`). All host functions are allowed if this is `None`, otherwise linking the plugin fails with
+    /// [`LinkError::CapabilityNotGranted`] if it imports any other host function.
+    pub capabilities: Option<Vec<String>>,
+    /// What happens to the plugin after it traps, defaults to the policy set with [`Plugs::with_failure_policy`]
+    pub failure_policy: FailurePolicy,
+    restarts: u32,
+    status: PlugStatus,
+    /// Status to go back to when the plugin is enabled again
+    enabled_status: PlugStatus,
+}
+
+impl<T> Plug<T> {
+    /// Returns how many times the plugin was restarted because of its [`FailurePolicy`]
+    pub fn restarts(&self) -> u32 {
+        self.restarts
+    }
+
+    /// Returns the current lifecycle status of the plugin
+    pub fn status(&self) -> &PlugStatus {
+        &self.status
+    }
+
+    fn set_disabled(&mut self) {
+        if self.status != PlugStatus::Disabled {
+            self.enabled_status = std::mem::replace(&mut self.status, PlugStatus::Disabled);
+        }
+    }
+}
+
+/// Lifecycle status of a plugin.
+///
+/// Plugins start out as `Loaded`, [`Plugs::link`] makes them `Linked` and [`Plugs::init`] makes them `Initialized`.
+/// Only initialized plugins can be called through `call` and `call_dynamic`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PlugStatus {
+    Loaded,
+    Linked,
+    Initialized,
+    /// Linking or initializing the plugin failed with the given reason. [`Plugs::link`] skips failed plugins until they
+    /// are reset with [`Plugs::reset_failed`].
+    Failed(String),
+    /// The plugin was disabled with [`Plugs::disable`] or because of its [`FailurePolicy`]. It keeps its instance, but
+    /// calls to it return [`PluginDisabled`] and `init` skips it.
+    Disabled,
+}
+
+impl std::fmt::Display for PlugStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PlugStatus::Loaded => write!(f, "loaded"),
+            PlugStatus::Linked => write!(f, "linked"),
+            PlugStatus::Initialized => write!(f, "initialized"),
+            PlugStatus::Failed(reason) => write!(f, "failed ({reason})"),
+            PlugStatus::Disabled => write!(f, "disabled"),
+        }
+    }
 }
 
 pub struct PlugMetadata {
     pub name: String,
+    /// Names of all dependencies in the order they were declared in, including optional ones
     pub deps: Vec<String>,
+    /// Names of the dependencies that were declared with a `?` prefix, these are allowed to be missing during linking
+    pub optional_deps: Vec<String>,
     pub exports: Vec<String>,
-    pub imports: Vec<String>,
+    pub imports: Vec<PlugImport>,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    /// Authors in the order they were declared in, the authors export separates them with semicolons (';')
+    pub authors: Vec<String>,
+    pub license: Option<String>,
+    pub homepage: Option<String>,
+}
+
+/// An import of a plugin that must be supplied by a host function or one of the plugin's dependencies.
+///
+/// Host functions are matched against the full (`module`, `name`, kind of `ty`) triple. If `module` is the name of one
+/// of the plugin's dependencies, the import is only resolved from that dependency, otherwise (e.g. for the default `env`
+/// module) it is resolved from the first dependency that has an export with the same name and kind.
+#[derive(Clone, Debug)]
+pub struct PlugImport {
+    pub module: String,
+    pub name: String,
+    pub ty: ExternType,
+}
+
+impl std::fmt::Display for PlugImport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}", self.module, self.name)
+    }
+}
+
+/// How imports from an optional dependency that isn't loaded (weak imports) are resolved if the host didn't add a
+/// default for them with [`Plugs::add_weak_default`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WeakImportPolicy {
+    /// Weak functions trap when they are called
+    #[default]
+    Trap,
+    /// Weak functions return zeroes
+    Zero,
+}
+
+/// What happens to a plugin after a call traps inside of it, since its memory may be left in an inconsistent state
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FailurePolicy {
+    /// Keep using the plugin
+    #[default]
+    Ignore,
+    /// Disable the plugin and all plugins that depend on it (see [`Plugs::disable_with_dependents`]), calling them
+    /// through `call` or `call_dynamic` returns [`PluginDisabled`] afterwards
+    Disable,
+    /// Instantiate the plugin again from its module and call its init export. Plugins that depend on it (directly or
+    /// indirectly) are instantiated and initialized again as well, since they are linked to the old instance.
+    ///
+    /// The plugin and its dependents are disabled instead once it was restarted `max_restarts` times or if restarting it
+    /// fails.
+    Restart { max_restarts: u32 },
 }
 
 pub struct PlugsResetOptions<T> {
@@ -61,7 +234,30 @@ pub struct Plugs<'a, T> {
     items: Vec<Plug<T>>,
     names: HashMap<String, PlugId>,
     host_fns: Vec<(String, String, Extern)>,
+    weak_defaults: Vec<(String, String, Extern)>,
+    weak_import_policy: WeakImportPolicy,
+    failure_policy: FailurePolicy,
+    /// Set while plugins are restarted, traps during the restart are handled by the restart itself
+    restarting: bool,
+    stats: Option<StatsRecorder>,
+    interceptors: Vec<Arc<dyn Interceptor>>,
+    /// Wrappers that run the interceptors around plugin exports called through [`Plugs::call`]
+    intercepted: HashMap<(PlugId, String), Func>,
+    config: SharedConfig,
+    assets: SharedAssets,
+    memories: SharedMemories,
+    /// Archives that plugins were loaded from
+    #[cfg(feature = "archive")]
+    archives: HashMap<PlugId, PlugArchive>,
+    /// ed25519 public keys that are trusted to sign plugins
+    #[cfg(feature = "verify")]
+    trusted_keys: Vec<[u8; 32]>,
     name_export: &'a str,
+    version_export: &'a str,
+    description_export: &'a str,
+    authors_export: &'a str,
+    license_export: &'a str,
+    homepage_export: &'a str,
     deps_export: &'a str,
     init_export: &'a str,
     reset_export: &'a str,
@@ -75,7 +271,26 @@ impl<'a, T> Plugs<'a, T> {
             items: Vec::new(),
             names: HashMap::new(),
             host_fns: Vec::new(),
+            weak_defaults: Vec::new(),
+            weak_import_policy: WeakImportPolicy::default(),
+            failure_policy: FailurePolicy::default(),
+            restarting: false,
+            stats: None,
+            interceptors: Vec::new(),
+            intercepted: HashMap::new(),
+            config: SharedConfig::default(),
+            assets: SharedAssets::default(),
+            memories: SharedMemories::default(),
+            #[cfg(feature = "archive")]
+            archives: HashMap::new(),
+            #[cfg(feature = "verify")]
+            trusted_keys: Vec::new(),
             name_export: DEFAULT_NAME_EXPORT,
+            version_export: DEFAULT_VERSION_EXPORT,
+            description_export: DEFAULT_DESCRIPTION_EXPORT,
+            authors_export: DEFAULT_AUTHORS_EXPORT,
+            license_export: DEFAULT_LICENSE_EXPORT,
+            homepage_export: DEFAULT_HOMEPAGE_EXPORT,
             deps_export: DEFAULT_DEPS_EXPORT,
             init_export: DEFAULT_INIT_EXPORT,
             reset_export: DEFAULT_RESET_EXPORT,
@@ -90,6 +305,46 @@ impl<'a, T> Plugs<'a, T> {
         }
     }
 
+    /// Change `version_export`
+    pub fn with_version(self, version_export: &'a str) -> Self {
+        Self {
+            version_export,
+            ..self
+        }
+    }
+
+    /// Change `description_export`
+    pub fn with_description(self, description_export: &'a str) -> Self {
+        Self {
+            description_export,
+            ..self
+        }
+    }
+
+    /// Change `authors_export`
+    pub fn with_authors(self, authors_export: &'a str) -> Self {
+        Self {
+            authors_export,
+            ..self
+        }
+    }
+
+    /// Change `license_export`
+    pub fn with_license(self, license_export: &'a str) -> Self {
+        Self {
+            license_export,
+            ..self
+        }
+    }
+
+    /// Change `homepage_export`
+    pub fn with_homepage(self, homepage_export: &'a str) -> Self {
+        Self {
+            homepage_export,
+            ..self
+        }
+    }
+
     /// Change `deps_export`
     pub fn with_deps(self, deps_export: &'a str) -> Self {
         Self {
@@ -114,6 +369,57 @@ impl<'a, T> Plugs<'a, T> {
         }
     }
 
+    /// Change `weak_import_policy`
+    pub fn with_weak_imports(self, weak_import_policy: WeakImportPolicy) -> Self {
+        Self {
+            weak_import_policy,
+            ..self
+        }
+    }
+
+    /// Change the `failure_policy` of plugins that are loaded afterwards, it can be changed per plugin with
+    /// [`Plug::failure_policy`]
+    pub fn with_failure_policy(self, failure_policy: FailurePolicy) -> Self {
+        Self {
+            failure_policy,
+            ..self
+        }
+    }
+
+    /// Enable or disable recording call statistics, see [`Plugs::stats`]
+    ///
+    /// Host functions are wrapped to record their calls, see [`Plugs::add_host_fn`] for how this affects their `Caller`.
+    pub fn with_stats(self, stats: bool) -> Self {
+        Self {
+            stats: stats.then(StatsRecorder::default),
+            ..self
+        }
+    }
+
+    /// Returns a snapshot of the call statistics if they were enabled with [`Plugs::with_stats`].
+    ///
+    /// Calls through `call`, `call_dynamic` and `init` are recorded for the called plugin, calls to host functions are
+    /// recorded for the calling plugin if they were linked while statistics were enabled.
+    pub fn stats(&self) -> Option<Stats> {
+        self.stats.as_ref().map(StatsRecorder::snapshot)
+    }
+
+    /// Clear all recorded call statistics
+    pub fn reset_stats(&mut self) {
+        if let Some(stats) = &self.stats {
+            stats.clear();
+        }
+    }
+
+    /// Add an interceptor that runs around every call made through `call` and `call_dynamic`. Calls to host functions
+    /// and calls between plugins are only intercepted if the interceptor was added before [`Plugs::link`].
+    ///
+    /// Host functions are wrapped to run the interceptors, see [`Plugs::add_host_fn`] for how this affects their `Caller`.
+    pub fn add_interceptor(&mut self, interceptor: impl Interceptor + 'static) {
+        self.interceptors.push(Arc::new(interceptor));
+        self.intercepted.clear();
+    }
+
     /// Returns a slice that contains loaded plugins in their load order
     /// This slice can be indexed with PlugId's to access plugins.
     pub fn items(&self) -> &[Plug<T>] {
@@ -137,6 +443,10 @@ impl<'a, T> Plugs<'a, T> {
     }
 
     /// Adds a new host function, function parameters and results are passed through the generic types
+    ///
+    /// If statistics or interceptors are enabled, host functions are called through a wrapper with `Func::call`. wasmtime
+    /// doesn't pass the plugin's `Caller` on in that case, so `Caller::get_export` returns `None` inside of the host
+    /// function. The [`BUILTIN_FNS`] aren't affected by this.
     pub fn add_host_fn<Params, Results>(
         &mut self,
         name: &str,
@@ -158,6 +468,37 @@ impl<'a, T> Plugs<'a, T> {
             .push((module.to_string(), name.to_string(), func));
     }
 
+    /// Adds a new host function in the given module whose type is only known at runtime (see [`wasmtime::Func::new`])
+    pub fn add_dynamic_host_fn(
+        &mut self,
+        name: &str,
+        module: &str,
+        ty: FuncType,
+        func: impl Fn(Caller<'_, PlugContext<T>>, &[Val], &mut [Val]) -> wasmtime::Result<()>
+            + Send
+            + Sync
+            + 'static,
+    ) {
+        let func = Func::new(&mut self.store, ty, func);
+        let func = Into::<Extern>::into(func);
+        self.host_fns
+            .push((module.to_string(), name.to_string(), func));
+    }
+
+    /// Adds a default for a weak import, which is used instead of `weak_import_policy` when the optional dependency
+    /// named `module` isn't loaded
+    pub fn add_weak_default<Params, Results>(
+        &mut self,
+        name: &str,
+        module: &str,
+        func: impl IntoFunc<PlugContext<T>, Params, Results>,
+    ) {
+        let func = Func::wrap(&mut self.store, func);
+        let func = Into::<Extern>::into(func);
+        self.weak_defaults
+            .push((module.to_string(), name.to_string(), func));
+    }
+
     /// Define host functions in the provided linker
     pub fn link_host(&mut self, linker: &mut Linker<PlugContext<T>>) -> wasmtime::Result<()> {
         for (module, name, func) in self.host_fns.iter() {
@@ -166,12 +507,22 @@ impl<'a, T> Plugs<'a, T> {
         Ok(())
     }
 
+    /// Returns whether the given import is provided by a host function with the same module, name and kind
+    fn is_host_import(&self, imp: &PlugImport) -> bool {
+        self.host_fns.iter().any(|(module, name, func)| {
+            *module == imp.module
+                && *name == imp.name
+                && ExportType::from(func.ty(&self.store)) == ExportType::from(&imp.ty)
+        })
+    }
+
     /// Extract metadata from the specified module by instantiating a temporary instance and running the
     /// necessary reserved functions (such as `deps`) for metadata extraction.
     ///
     /// # Errors
     ///
     /// - Returns [`ExportNotFound`] if a required export couldn't be found.
+    /// - Returns [`MetadataError`] if a metadata export returned an invalid string or an invalid plugin name.
     /// - May return `wasmtime` errors from [`wasmtime::Linker::instantiate`] or [`wasmtime::TypedFunc::call`] or other `wasmtime` APIs used internally.
     pub fn extract_metadata(
         &mut self,
@@ -181,22 +532,25 @@ impl<'a, T> Plugs<'a, T> {
     ) -> wasmtime::Result<PlugMetadata> {
         let imports = module
             .imports()
-            .into_iter()
-            .filter_map(|imp| {
-                let is_host_fn = self.host_fns.iter().any(|(_, name, _)| name.eq(imp.name()));
-                if !is_host_fn {
-                    Some(imp.name().to_string())
-                } else {
-                    None
-                }
+            .map(|imp| PlugImport {
+                module: imp.module().to_string(),
+                name: imp.name().to_string(),
+                ty: imp.ty(),
             })
             .collect();
         let exports = module.exports().map(|e| e.name().to_string()).collect();
 
         let mut linker = Linker::new(engine);
-        linker.define_unknown_imports_as_traps(&module)?;
+        linker.define_unknown_imports_as_traps(module)?;
 
-        let instance = linker.instantiate(&mut self.store, &module)?;
+        // Other kinds of imports get placeholder values since metadata exports aren't supposed to use them
+        for imp in module.imports() {
+            if let Some(placeholder) = placeholder(&mut self.store, &imp.ty())? {
+                linker.define(&mut self.store, imp.module(), imp.name(), placeholder)?;
+            }
+        }
+
+        let instance = linker.instantiate(&mut self.store, module)?;
 
         let memory = if let Some(m) = instance.get_export(&mut self.store, "memory") {
             if let Some(m) = m.into_memory() {
@@ -218,53 +572,91 @@ impl<'a, T> Plugs<'a, T> {
             .into());
         };
 
-        // Extract dependencies (optional)
+        // Extract dependencies (optional), empty entries (e.g. from a trailing ';') are ignored
         let mut deps = Vec::new();
-        if let Ok(deps_fn) = instance.get_typed_func::<(), u32>(&mut self.store, self.deps_export) {
-            self.set_current_id(id);
-            let mut deps_ptr = deps_fn.call(&mut self.store, ())? as usize;
-            deps.push(String::new());
-            let memory = memory.data(&mut self.store);
-            while memory[deps_ptr] != 0 {
-                let c = memory[deps_ptr] as char;
-                if c == ';' {
-                    deps.push(String::new());
-                } else {
-                    deps.last_mut().unwrap().push(c)
+        let mut optional_deps = Vec::new();
+        let deps_export = self.deps_export;
+        if let Some(deps_str) = self.call_metadata_export(&instance, memory, deps_export, id)? {
+            for dep in deps_str.split(';').filter(|dep| !dep.is_empty()) {
+                let dep_name = dep.strip_prefix('?').unwrap_or(dep);
+                validate_name(deps_export, dep_name)?;
+                if dep_name.len() != dep.len() {
+                    optional_deps.push(dep_name.to_string());
                 }
-                deps_ptr += 1;
+                deps.push(dep_name.to_string());
             }
         }
 
-        let mut name = String::new();
-        match instance.get_typed_func::<(), u32>(&mut self.store, self.name_export) {
-            Ok(name_fn) => {
-                self.set_current_id(id);
-                let mut name_ptr = name_fn.call(&mut self.store, ())? as usize;
-                let memory = memory.data(&mut self.store);
-                while memory[name_ptr] != 0 {
-                    name.push(memory[name_ptr] as char);
-                    name_ptr += 1;
-                }
-            }
-            Err(_) => {
-                return Err(ExportNotFound {
-                    export_name: self.name_export.to_string(),
-                    plug_name: format!("<no-name>, id:{id}"),
-                    expected_ty: ExportType::Func,
-                }
-                .into());
+        let name_export = self.name_export;
+        let Some(name) = self.call_metadata_export(&instance, memory, name_export, id)? else {
+            return Err(ExportNotFound {
+                export_name: name_export.to_string(),
+                plug_name: format!("<no-name>, id:{id}"),
+                expected_ty: ExportType::Func,
             }
-        }
+            .into());
+        };
+        validate_name(name_export, &name)?;
+
+        // Extract version (optional)
+        let version_export = self.version_export;
+        let version = self.call_metadata_export(&instance, memory, version_export, id)?;
+
+        // Extract descriptive metadata (optional)
+        let description_export = self.description_export;
+        let description = self.call_metadata_export(&instance, memory, description_export, id)?;
+        let authors_export = self.authors_export;
+        let authors = self
+            .call_metadata_export(&instance, memory, authors_export, id)?
+            .map(|authors| {
+                authors
+                    .split(';')
+                    .map(str::trim)
+                    .filter(|author| !author.is_empty())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        let license_export = self.license_export;
+        let license = self.call_metadata_export(&instance, memory, license_export, id)?;
+        let homepage_export = self.homepage_export;
+        let homepage = self.call_metadata_export(&instance, memory, homepage_export, id)?;
 
         Ok(PlugMetadata {
             name,
+            version,
+            description,
+            authors,
+            license,
+            homepage,
             deps,
+            optional_deps,
             exports,
             imports,
         })
     }
 
+    /// Call a metadata export that returns a pointer to a NUL-terminated UTF-8 string and read that string, returns
+    /// `None` if the plugin doesn't have the export
+    fn call_metadata_export(
+        &mut self,
+        instance: &Instance,
+        memory: Memory,
+        export: &str,
+        id: PlugId,
+    ) -> wasmtime::Result<Option<String>> {
+        let Ok(func) = instance.get_typed_func::<(), u32>(&mut self.store, export) else {
+            return Ok(None);
+        };
+        self.set_current_id(id);
+        let ptr = func.call(&mut self.store, ())?;
+        Ok(Some(read_metadata_str(
+            memory.data(&self.store),
+            export,
+            ptr,
+        )?))
+    }
+
     /// Load wasm module and add it to the list of plugins.
     /// Doesn't perform any linking.
     /// Returns the id of the loaded plugin if load was successful
@@ -274,9 +666,19 @@ impl<'a, T> Plugs<'a, T> {
     /// - Returns [`PluginAlreadyExists`] if the requested plugin name already exists.
     /// - May return [`ExportNotFound`] or other `wasmtime` errors via [`Plugs::extract_metadata`].
     pub fn load_module(&mut self, module: Module, engine: &Engine) -> wasmtime::Result<PlugId> {
-        let id = self.names.len();
-        let metadata = self.extract_metadata(engine, &module, id)?;
+        let metadata = self.extract_metadata(engine, &module, self.names.len())?;
+        self.add_plug(module, metadata, engine)
+    }
 
+    /// Add a plugin whose metadata was already extracted with [`Plugs::extract_metadata`], so that its module isn't
+    /// instantiated again
+    pub(crate) fn add_plug(
+        &mut self,
+        module: Module,
+        metadata: PlugMetadata,
+        engine: &Engine,
+    ) -> wasmtime::Result<PlugId> {
+        let id = self.names.len();
         if self.names.contains_key(&metadata.name) {
             return Err(PluginAlreadyExists {
                 name: metadata.name,
@@ -290,8 +692,20 @@ impl<'a, T> Plugs<'a, T> {
             linker: Linker::new(engine),
             instance: None,
             deps: metadata.deps,
+            optional_deps: metadata.optional_deps,
             exports: metadata.exports,
             imports: metadata.imports,
+            version: metadata.version,
+            description: metadata.description,
+            authors: metadata.authors,
+            license: metadata.license,
+            homepage: metadata.homepage,
+            fuel_limit: None,
+            capabilities: None,
+            failure_policy: self.failure_policy,
+            restarts: 0,
+            status: PlugStatus::Loaded,
+            enabled_status: PlugStatus::Loaded,
         });
         self.names.insert(metadata.name, id);
 
@@ -314,121 +728,649 @@ impl<'a, T> Plugs<'a, T> {
         self.load_module(module, engine)
     }
 
+    /// Load plugin from WebAssembly text format and return its id (see `load_module`)
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`WatError`] with the line and column of the problem if `wat` couldn't be parsed.
+    /// - May return any error [`Plugs::load_binary`] returns.
+    #[cfg(feature = "wat")]
+    pub fn load_wat(&mut self, wat: &str, engine: &Engine) -> wasmtime::Result<PlugId> {
+        let bin = wat::parse(wat, None)?;
+
+        self.load_binary(bin, engine)
+    }
+
     /// Load plugin from the file system and return its id (see `load_module`)
     ///
+    /// With the `wat` feature, files with the `.wat` extension are parsed as WebAssembly text format (see [`Plugs::load_wat`]).
+    /// Without it, files are passed to [`wasmtime::Module::new`] as they are, which also accepts the text format as long
+    /// as wasmtime's own `wat` feature is enabled (it is by default). The feature only adds `WatError`s with the line
+    /// and column of parse errors.
+    ///
     /// # Errors
     ///
     /// - May return [`PluginAlreadyExists`], [`ExportNotFound`] or other `wasmtime` errors via [`Plugs::extract_metadata`].
     /// - May return `wasmtime` errors from [`wasmtime::Module::from_file`].
+    /// - May return [`std::io::Error`]s or [`WatError`] when loading a `.wat` file with the `wat` feature.
     pub fn load(
         &mut self,
         file_path: impl AsRef<Path>,
         engine: &Engine,
     ) -> wasmtime::Result<PlugId> {
-        let module = Module::from_file(engine, file_path)?;
+        #[cfg(feature = "archive")]
+        if file_path
+            .as_ref()
+            .extension()
+            .is_some_and(|ext| ext == "wlug")
+        {
+            return self.load_archive(file_path, engine);
+        }
+
+        let module = read_module(file_path.as_ref(), engine)?;
 
         self.load_module(module, engine)
     }
 
-    /// Link all plugins with host functions and each other, load order is important (TODO: auto sorting)
+    /// Load all `.wasm` files (and `.wat` files with the `wat` feature and `.wlug` files with the `archive` feature) inside a directory in the order of their file names
+    /// and return their ids (see `load`). Since load order is important, the file names of dependencies need to come before
+    /// their dependents.
+    ///
+    /// # Errors
+    ///
+    /// - May return [`std::io::Error`]s from reading the directory.
+    /// - May return any error [`Plugs::load`] returns.
+    pub fn load_dir(
+        &mut self,
+        dir_path: impl AsRef<Path>,
+        engine: &Engine,
+    ) -> wasmtime::Result<Vec<PlugId>> {
+        let mut paths = Vec::new();
+        for entry in std::fs::read_dir(dir_path)? {
+            let path = entry?.path();
+            let is_plug = path.extension().is_some_and(|ext| {
+                ext == "wasm"
+                    || (cfg!(feature = "wat") && ext == "wat")
+                    || (cfg!(feature = "archive") && ext == "wlug")
+            });
+            if path.is_file() && is_plug {
+                paths.push(path);
+            }
+        }
+        paths.sort();
+
+        let mut ids = Vec::with_capacity(paths.len());
+        for path in paths {
+            ids.push(self.load(path, engine)?);
+        }
+        Ok(ids)
+    }
+
+    /// Link all plugins that are [`PlugStatus::Loaded`] with host functions and each other, load order is important (TODO: auto sorting)
     /// and circular dependencies are disallowed (won't change, TODO: report as error)
     ///
+    /// All imports are resolved with the same logic as [`Plugs::plan_link`] before anything is instantiated.
+    ///
     /// # Errors
     ///
-    /// - Returns [`LinkError`] in the case of a linker specific error. (See [`LinkError`] for more details.)
+    /// - Returns the first [`LinkError`] of [`Plugs::plan_link`] in the case of a linker specific error. (See [`LinkError`] for more details.)
+    /// - Returns [`LinkError::SignatureMismatch`] if an imported function's type doesn't match the type of the function that provides it.
     /// - May return `wasmtime` errors from [`wasmtime::Linker::define`] or [`wasmtime::Linker::instantiate`].
     pub fn link(&mut self) -> wasmtime::Result<()> {
-        // TODO: perhaps sort the plugins before linking them so that all plugins are guaranteed to be loaded after their dependencies
-        // this could also be a chance for us to detect circular dependencies and throw an error in that case since they are disallowed
-        //
-        // Circular dependencies are disallowed because we can't easily detect which _symbol_ depends on which, we only know which plugin
-        // depends on which symbols and that isn't really enough to properly resolve all cases. If we were to just use that info, there
-        // could be some edge case where the linker doesn't properly link everything especially if the dependency graph is very
-        // convoluted and the circular dependency is deep within the dependency tree.
-        for p_id in 0..self.items.len() {
-            // Link host functions
-            for (module, name, func) in self.host_fns.iter() {
-                self.items[p_id]
-                    .linker
-                    .define(&mut self.store, module, name, func.clone())?;
-            }
-
-            let p = &self.items[p_id];
-            let deps = p.deps.clone();
-            let mut imports = p.imports.clone();
-            let mut to_import = Vec::new();
+        let plan = self.plan_link();
+        self.intercepted.clear();
+        if let Some(e) = plan.errors().next() {
+            return Err(e.clone().into());
+        }
 
-            // #[cfg(debug_assertions)]
-            // println!("\n[Plugs::link]: '{name}' has {deps:?} as dependencies");
-
-            if imports.len() > 0 {
-                for dep_name in deps.iter() {
-                    if let Some(&p_dep_id) = self.names.get(dep_name) {
-                        imports = {
-                            let mut res = Vec::new();
-                            for imp in imports {
-                                let exists = self.items[p_dep_id].exports.contains(&imp);
-                                if exists {
-                                    let inst = if let Some(inst) = &self.items[p_dep_id].instance {
-                                        inst
-                                    } else {
-                                        return Err(LinkError::NotInstantiated {
-                                            dep_name: dep_name.clone(),
-                                            plug_name: p.name.clone(),
-                                        }
-                                        .into());
-                                    };
-
-                                    let export =
-                                        if let Some(e) = inst.get_export(&mut self.store, &imp) {
-                                            e
-                                        } else {
-                                            return Err(LinkError::ExportNotFound {
-                                                dep_name: dep_name.clone(),
-                                                export_name: imp,
-                                                plug_name: p.name.clone(),
-                                            }
-                                            .into());
-                                        };
-
-                                    // #[cfg(debug_assertions)]
-                                    // println!("[Plugs::link]: Will define '{imp}' from '{dep_name}' in '{name}'");
-
-                                    to_import.push((imp, export));
-                                } else {
-                                    res.push(imp);
-                                }
-                            }
-
-                            res
-                        };
-                    } else {
-                        return Err(LinkError::DependencyNotFound(dep_name.clone()).into());
+        for p_plan in plan.plugs.iter() {
+            if self.items[p_plan.id].status != PlugStatus::Loaded {
+                continue;
+            }
+            if let Err(e) = self.instantiate_plug(p_plan) {
+                self.items[p_plan.id].status = PlugStatus::Failed(e.to_string());
+                return Err(e);
+            }
+            self.items[p_plan.id].status = PlugStatus::Linked;
+        }
+        Ok(())
+    }
+
+    /// Define everything a plugin imports according to its plan in its linker and instantiate it
+    fn instantiate_plug(&mut self, plan: &PlugLinkPlan) -> wasmtime::Result<()> {
+        let p_id = plan.id;
+
+        // Link host functions
+        for (module, name, func) in self.host_fns.clone() {
+            let func = self.instrument_host_fn(&plan.name, &module, &name, func);
+            let func = self.intercept_host_fn(p_id, &module, &name, func);
+            self.items[p_id]
+                .linker
+                .define(&mut self.store, &module, &name, func)?;
+        }
+
+        let present_deps = self.items[p_id]
+            .deps
+            .iter()
+            .filter(|dep_name| self.names.contains_key(*dep_name))
+            .cloned()
+            .collect();
+        for (name, func) in builtins::funcs(
+            &mut self.store,
+            p_id,
+            present_deps,
+            self.config.clone(),
+            self.assets.clone(),
+            self.memories.clone(),
+        ) {
+            let func = self.instrument_host_fn(&plan.name, BUILTIN_MODULE, name, func.into());
+            let func = self.intercept_host_fn(p_id, BUILTIN_MODULE, name, func);
+            self.items[p_id]
+                .linker
+                .define(&mut self.store, BUILTIN_MODULE, name, func)?;
+        }
+
+        for resolved in plan.imports.iter() {
+            let imp = &resolved.import;
+            let export = match &resolved.source {
+                ImportSource::Host | ImportSource::Builtin => continue,
+                ImportSource::Dep { dep, id } => {
+                    let inst =
+                        self.items[*id]
+                            .instance
+                            .ok_or_else(|| LinkError::NotInstantiated {
+                                dep_name: dep.clone(),
+                                plug_name: plan.name.clone(),
+                            })?;
+                    let export = inst.get_export(&mut self.store, &imp.name).ok_or_else(|| {
+                        LinkError::ExportNotFound {
+                            dep_name: dep.clone(),
+                            export_name: imp.name.clone(),
+                            plug_name: plan.name.clone(),
+                        }
+                    })?;
+                    match export {
+                        Extern::Func(func) if !self.interceptors.is_empty() => {
+                            let callee = Callee::Plug {
+                                id: *id,
+                                name: dep.clone(),
+                                func: imp.name.clone(),
+                            };
+                            intercept::wrap_func(
+                                &mut self.store,
+                                self.interceptors.clone(),
+                                Some(p_id),
+                                callee,
+                                func,
+                            )
+                            .into()
+                        }
+                        export => export,
                     }
                 }
+                ImportSource::WeakDefault { .. } => self
+                    .weak_defaults
+                    .iter()
+                    .find(|(module, name, _)| *module == imp.module && *name == imp.name)
+                    .map(|(_, _, func)| func.clone())
+                    .map(|func| self.instrument_host_fn(&plan.name, &imp.module, &imp.name, func))
+                    .map(|func| self.intercept_host_fn(p_id, &imp.module, &imp.name, func))
+                    .ok_or_else(|| LinkError::UnresolvedImports {
+                        plug_name: plan.name.clone(),
+                        unresolved_imports: vec![imp.clone()],
+                    })?,
+                ImportSource::WeakStub { .. } => {
+                    weak_stub(&mut self.store, self.weak_import_policy, imp)?
+                }
+            };
+
+            // #[cfg(debug_assertions)]
+            // println!("[Plugs::link]: Will define '{imp}' from '{}' in '{}'", resolved.source, plan.name);
+
+            self.items[p_id]
+                .linker
+                .define(&mut self.store, &imp.module, &imp.name, export)?;
+        }
+
+        // Drop the memory of a previous instance so builtins called by the start function don't use it
+        self.memories.remove(p_id);
+        let p = &mut self.items[p_id];
+        let instance = p.linker.instantiate(&mut self.store, &p.module)?;
+        p.instance = Some(instance);
+        if let Some(memory) = instance.get_memory(&mut self.store, "memory") {
+            self.memories.set(p_id, memory);
+        }
+        Ok(())
+    }
+
+    /// Wrap a host function that is linked into the plugin named `plug` to record its calls if statistics are enabled
+    fn instrument_host_fn(&mut self, plug: &str, module: &str, name: &str, func: Extern) -> Extern {
+        match (&self.stats, func) {
+            (Some(stats), Extern::Func(func)) => stats
+                .wrap_host_fn(&mut self.store, plug, &format!("{module}.{name}"), func)
+                .into(),
+            (_, func) => func,
+        }
+    }
+
+    /// Wrap a host function that is linked into the plugin `caller` to run the interceptors around its calls
+    fn intercept_host_fn(
+        &mut self,
+        caller: PlugId,
+        module: &str,
+        name: &str,
+        func: Extern,
+    ) -> Extern {
+        match func {
+            Extern::Func(func) if !self.interceptors.is_empty() => {
+                let callee = Callee::Host {
+                    module: module.to_string(),
+                    name: name.to_string(),
+                };
+                let interceptors = self.interceptors.clone();
+                intercept::wrap_func(&mut self.store, interceptors, Some(caller), callee, func)
+                    .into()
             }
+            func => func,
+        }
+    }
 
-            if imports.len() > 0 {
-                return Err(LinkError::UnresolvedImports {
-                    plug_name: p.name.clone(),
-                    unresolved_imports: imports,
+    /// Returns a wrapper of the export `func` of plugin `id` that runs the interceptors around calls from the host
+    fn intercepted_func(&mut self, id: PlugId, func: &str) -> wasmtime::Result<Func> {
+        if let Some(f) = self.intercepted.get(&(id, func.to_string())) {
+            return Ok(*f);
+        }
+
+        let p = self.items.get(id).ok_or(UnknownPlugin::Id(id))?;
+        let export_not_found = || ExportNotFound {
+            export_name: func.to_string(),
+            plug_name: p.name.clone(),
+            expected_ty: ExportType::Func,
+        };
+        if !p.exports.contains(&func.to_string()) {
+            return Err(export_not_found().into());
+        }
+        let inst = p.instance.ok_or_else(|| {
+            wasmtime::Error::msg(format!("Plugin '{}' hasn't been instantiated yet", p.name))
+        })?;
+        let f = inst
+            .get_func(&mut self.store, func)
+            .ok_or_else(export_not_found)?;
+
+        let callee = Callee::Plug {
+            id,
+            name: p.name.clone(),
+            func: func.to_string(),
+        };
+        let wrapper =
+            intercept::wrap_func(&mut self.store, self.interceptors.clone(), None, callee, f);
+        self.intercepted.insert((id, func.to_string()), wrapper);
+        Ok(wrapper)
+    }
+
+    /// Run a call into the function `func` of plugin `id`, record it if statistics are enabled and attach a [`PlugTrap`]
+    /// to the error if the call trapped
+    fn run_call<R>(
+        &mut self,
+        id: PlugId,
+        func: &str,
+        call: impl FnOnce(&mut Store<PlugContext<T>>) -> wasmtime::Result<R>,
+    ) -> wasmtime::Result<R> {
+        self.set_current_id(id);
+        // Calls into plugins with a fuel limit get their own fuel, the fuel of the outer call is restored afterwards
+        let outer_fuel = match self.items[id].fuel_limit {
+            Some(limit) => {
+                let outer = self.store.get_fuel().map_err(|e| {
+                    e.context(format!(
+                        "Plugin '{}' has a fuel limit but the engine doesn't consume fuel",
+                        self.items[id].name
+                    ))
+                })?;
+                self.store.set_fuel(limit)?;
+                Some(outer)
+            }
+            None => None,
+        };
+        let fuel = self.store.get_fuel().ok();
+        let start = Instant::now();
+        let res = call(&mut self.store);
+
+        if let Some(stats) = &self.stats {
+            let elapsed = start.elapsed();
+            let fuel_used = fuel
+                .zip(self.store.get_fuel().ok())
+                .map_or(0, |(before, after)| before.saturating_sub(after));
+            let trapped = res.as_ref().is_err_and(|e| e.is::<Trap>());
+            stats.record_call(&self.items[id].name, func, elapsed, trapped, fuel_used);
+        }
+        if let Some(outer) = outer_fuel {
+            let _ = self.store.set_fuel(outer);
+        }
+
+        let res = res.map_err(|e| self.trap_context(id, func, e));
+        let failed = res.as_ref().err().and_then(|e| {
+            let trap = e
+                .downcast_ref::<PlugTrap>()
+                .filter(|trap| trap.trap.is_some())?;
+            // The innermost plugin is the one that trapped
+            let failed = trap
+                .frames
+                .iter()
+                .find_map(|frame| frame.plug.as_deref())
+                .and_then(|name| self.get_id(name));
+            Some(failed.unwrap_or(id))
+        });
+        if let Some(failed) = failed {
+            self.handle_failure(failed);
+        }
+        res
+    }
+
+    /// Apply the failure policy of a plugin that trapped
+    fn handle_failure(&mut self, id: PlugId) {
+        if self.restarting {
+            return;
+        }
+        let p = &mut self.items[id];
+        let disable = match p.failure_policy {
+            FailurePolicy::Ignore => false,
+            FailurePolicy::Disable => true,
+            FailurePolicy::Restart { max_restarts } if p.restarts >= max_restarts => true,
+            FailurePolicy::Restart { .. } => {
+                p.restarts += 1;
+                self.restarting = true;
+                let res = self.restart(id);
+                self.restarting = false;
+                res.is_err()
+            }
+        };
+        // Dependents are linked to the failed instance, so they are disabled along with it
+        if disable {
+            let name = self.items[id].name.clone();
+            let _ = self.disable_with_dependents(&name);
+        }
+    }
+
+    /// Instantiate a plugin and its dependents again with fresh linkers and call their init exports. Dependents keep
+    /// their status, so only the ones that were initialized are initialized again.
+    fn restart(&mut self, id: PlugId) -> wasmtime::Result<()> {
+        let mut ids = vec![id];
+        for (p_id, p) in self.items.iter().enumerate().skip(id + 1) {
+            let depends = p.deps.iter().any(|dep| {
+                self.names
+                    .get(dep)
+                    .is_some_and(|dep_id| ids.contains(dep_id))
+            });
+            if depends {
+                ids.push(p_id);
+            }
+        }
+
+        let engine = self.store.engine().clone();
+        let mut statuses = Vec::with_capacity(ids.len());
+        for &p_id in ids.iter() {
+            let p = &mut self.items[p_id];
+            p.linker = Linker::new(&engine);
+            p.instance = None;
+            statuses.push(std::mem::replace(&mut p.status, PlugStatus::Loaded));
+        }
+        statuses[0] = PlugStatus::Initialized;
+        self.intercepted.retain(|(p_id, _), _| !ids.contains(p_id));
+
+        for (&p_id, status) in ids.iter().zip(statuses) {
+            let plan = self.plan_plug(p_id);
+            let res = match plan.errors.first() {
+                Some(e) => Err(e.clone().into()),
+                None => self.instantiate_plug(&plan),
+            };
+            let res = res.and_then(|_| {
+                if status == PlugStatus::Initialized {
+                    self.call_init(p_id)?;
                 }
-                .into());
+                Ok(())
+            });
+
+            self.items[p_id].status = match res {
+                Ok(()) if status == PlugStatus::Loaded => PlugStatus::Linked,
+                Ok(()) => status,
+                Err(e) => {
+                    self.items[p_id].status = PlugStatus::Failed(e.to_string());
+                    return Err(e);
+                }
+            };
+        }
+        Ok(())
+    }
+
+    /// Returns an error if the plugin isn't initialized, [`PluginDisabled`] if it was disabled and
+    /// [`InvalidPlugStatus`] otherwise
+    fn check_initialized(&self, id: PlugId) -> wasmtime::Result<()> {
+        let p = self.items.get(id).ok_or(UnknownPlugin::Id(id))?;
+        match &p.status {
+            PlugStatus::Initialized => Ok(()),
+            PlugStatus::Disabled => Err(PluginDisabled {
+                name: p.name.clone(),
+            }
+            .into()),
+            status => Err(InvalidPlugStatus {
+                name: p.name.clone(),
+                status: status.clone(),
+                expected: PlugStatus::Initialized,
+            }
+            .into()),
+        }
+    }
+
+    /// Attach a [`PlugTrap`] to an error that was returned from wasm code
+    fn trap_context(&self, id: PlugId, func: &str, e: wasmtime::Error) -> wasmtime::Error {
+        let trap = e.downcast_ref::<Trap>().copied();
+        let backtrace = e.downcast_ref::<WasmBacktrace>();
+        if trap.is_none() && backtrace.is_none() {
+            return e;
+        }
+
+        let frames = backtrace
+            .map(|backtrace| {
+                backtrace
+                    .frames()
+                    .iter()
+                    .map(|frame| TrapFrame {
+                        plug: self
+                            .items
+                            .iter()
+                            .find(|p| p.module.image_range() == frame.module().image_range())
+                            .map(|p| p.name.clone()),
+                        func_index: frame.func_index(),
+                        func_name: frame.func_name().map(str::to_string),
+                        module_offset: frame.module_offset(),
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        e.context(PlugTrap {
+            plug_name: self.items[id].name.clone(),
+            func_name: func.to_string(),
+            trap,
+            frames,
+        })
+    }
+
+    /// Disable a plugin without unloading it, see [`PlugStatus::Disabled`]
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`UnknownPlugin::Name`] if the plugin couldn't be found.
+    /// - Returns [`ToggleError::HasDependents`] if plugins that aren't disabled depend on it, use
+    ///   [`Plugs::disable_with_dependents`] to disable them as well.
+    pub fn disable(&mut self, name: &str) -> wasmtime::Result<()> {
+        let id = self
+            .get_id(name)
+            .ok_or_else(|| UnknownPlugin::Name(name.to_string()))?;
+
+        let dependents = self
+            .items
+            .iter()
+            .filter(|p| p.status != PlugStatus::Disabled && p.deps.iter().any(|dep| dep == name))
+            .map(|p| p.name.clone())
+            .collect::<Vec<_>>();
+        if !dependents.is_empty() {
+            return Err(ToggleError::HasDependents {
+                name: name.to_string(),
+                dependents,
             }
+            .into());
+        }
 
+        self.items[id].set_disabled();
+        Ok(())
+    }
+
+    /// Disable a plugin and all plugins that depend on it (directly or indirectly) and return the names of the plugins
+    /// that were disabled
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`UnknownPlugin::Name`] if the plugin couldn't be found.
+    pub fn disable_with_dependents(&mut self, name: &str) -> wasmtime::Result<Vec<String>> {
+        let id = self
+            .get_id(name)
+            .ok_or_else(|| UnknownPlugin::Name(name.to_string()))?;
+
+        let mut ids = vec![id];
+        let mut i = 0;
+        while i < ids.len() {
+            let dep_name = &self.items[ids[i]].name;
+            for (p_id, p) in self.items.iter().enumerate() {
+                if !ids.contains(&p_id) && p.deps.contains(dep_name) {
+                    ids.push(p_id);
+                }
+            }
+            i += 1;
+        }
+
+        let mut disabled = Vec::new();
+        for p_id in ids {
             let p = &mut self.items[p_id];
-            for (imp, export) in to_import {
-                p.linker.define(&mut self.store, "env", &imp, export)?;
+            if p.status != PlugStatus::Disabled {
+                p.set_disabled();
+                disabled.push(p.name.clone());
+            }
+        }
+        Ok(disabled)
+    }
+
+    /// Enable a disabled plugin, it goes back to the status it had before it was disabled
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`UnknownPlugin::Name`] if the plugin couldn't be found.
+    /// - Returns [`ToggleError::DependencyDisabled`] if one of its dependencies is disabled.
+    pub fn enable(&mut self, name: &str) -> wasmtime::Result<()> {
+        let id = self
+            .get_id(name)
+            .ok_or_else(|| UnknownPlugin::Name(name.to_string()))?;
+
+        let p = &self.items[id];
+        let disabled_dep = p.deps.iter().find(|dep| {
+            self.get_plug(dep)
+                .is_some_and(|dep| dep.status == PlugStatus::Disabled)
+        });
+        if let Some(dep) = disabled_dep {
+            return Err(ToggleError::DependencyDisabled {
+                name: name.to_string(),
+                dep: dep.clone(),
             }
+            .into());
+        }
 
-            p.instance = Some(p.linker.instantiate(&mut self.store, &p.module)?);
+        let p = &mut self.items[id];
+        if p.status == PlugStatus::Disabled {
+            p.status = p.enabled_status.clone();
         }
         Ok(())
     }
 
+    /// Reset a [`PlugStatus::Failed`] plugin and the failed plugins that depend on it (directly or indirectly) to
+    /// [`PlugStatus::Loaded`] so that the next [`Plugs::link`] and [`Plugs::init`] try them again. Their instances are
+    /// dropped and they get fresh linkers. Returns the names of the plugins that were reset, which is empty if the
+    /// plugin hadn't failed.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`UnknownPlugin::Name`] if the plugin couldn't be found.
+    pub fn reset_failed(&mut self, name: &str) -> wasmtime::Result<Vec<String>> {
+        let id = self
+            .get_id(name)
+            .ok_or_else(|| UnknownPlugin::Name(name.to_string()))?;
+        let failed = |p: &Plug<T>| matches!(p.status, PlugStatus::Failed(_));
+        if !failed(&self.items[id]) {
+            return Ok(Vec::new());
+        }
+
+        let mut ids = vec![id];
+        let mut i = 0;
+        while i < ids.len() {
+            let dep_name = &self.items[ids[i]].name;
+            for (p_id, p) in self.items.iter().enumerate() {
+                if !ids.contains(&p_id) && failed(p) && p.deps.contains(dep_name) {
+                    ids.push(p_id);
+                }
+            }
+            i += 1;
+        }
+
+        let engine = self.store.engine().clone();
+        let mut reset = Vec::with_capacity(ids.len());
+        for &p_id in ids.iter() {
+            let p = &mut self.items[p_id];
+            p.linker = Linker::new(&engine);
+            p.instance = None;
+            p.status = PlugStatus::Loaded;
+            p.enabled_status = PlugStatus::Loaded;
+            reset.push(p.name.clone());
+        }
+        self.intercepted.retain(|(p_id, _), _| !ids.contains(p_id));
+        Ok(reset)
+    }
+
+    /// Replace the configuration of a plugin, plugins can read their configuration with the `config_get_*` functions of
+    /// [`BUILTIN_MODULE`] (see [`BUILTIN_FNS`]).
+    ///
+    /// ```no_run
+    /// # use wlug::{Plugs, wasmtime::Engine};
+    /// # let mut plugs = Plugs::new(&Engine::default(), ());
+    /// plugs.set_config("plug1", [("greeting", "hello".into()), ("retries", 3.into())])?;
+    /// # wlug::wasmtime::Result::<()>::Ok(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`UnknownPlugin`] if no plugin named `name` is loaded.
+    pub fn set_config<K: Into<String>>(
+        &mut self,
+        name: &str,
+        config: impl IntoIterator<Item = (K, ConfigValue)>,
+    ) -> wasmtime::Result<()> {
+        let id = self
+            .get_id(name)
+            .ok_or_else(|| UnknownPlugin::Name(name.to_string()))?;
+        self.config
+            .set(id, config.into_iter().map(|(k, v)| (k.into(), v)).collect());
+        Ok(())
+    }
+
+    /// Returns the value of a plugin's config key
+    pub fn get_config(&self, name: &str, key: &str) -> Option<ConfigValue> {
+        self.config.get(self.get_id(name)?, key)
+    }
+
     /// Reset `self` by clearing all plugins and calling their (optional) reset exports but doesn't reset the state inside `self.store`
     pub fn reset(&mut self) -> wasmtime::Result<()> {
         for (id, p) in self.items.iter_mut().enumerate() {
+            if p.status == PlugStatus::Disabled {
+                continue;
+            }
             if let Some(inst) = &p.instance {
                 if let Ok(reset_fn) =
                     inst.get_typed_func::<(), ()>(&mut self.store, self.reset_export)
@@ -440,6 +1382,12 @@ impl<'a, T> Plugs<'a, T> {
         }
         self.items.clear();
         self.names.clear();
+        self.intercepted.clear();
+        self.config.clear();
+        self.assets.clear();
+        self.memories.clear();
+        #[cfg(feature = "archive")]
+        self.archives.clear();
         Ok(())
     }
 
@@ -453,6 +1401,7 @@ impl<'a, T> Plugs<'a, T> {
         }
         if options.host_fns {
             self.host_fns.clear();
+            self.weak_defaults.clear();
         }
 
         Ok(())
@@ -470,19 +1419,121 @@ impl<'a, T> Plugs<'a, T> {
 
     /// Call the init functions of all plugins. This method looks for an export with the same name as `self.init_export` in each plugin.
     /// As an init export is optional in plugins, this method will just skip plugins without an init export.
+    ///
+    /// Dependencies are initialized before their dependents, otherwise plugins are initialized in load order.
+    /// Plugins that are already initialized are skipped.
+    ///
+    /// # Errors
+    ///
+    /// - Returns an [`InitError`] for the first plugin whose init export failed, the remaining plugins aren't initialized.
+    ///   The error is [`InvalidPlugStatus`] if the plugin wasn't linked.
+    ///
+    /// Disabled plugins are skipped, they are initialized once they are enabled and `init` is called again.
     pub fn init(&mut self) -> wasmtime::Result<()> {
-        let names = self
-            .names
-            .keys()
-            .map(|s| s.clone())
-            .collect::<Vec<String>>();
-        for name in names {
-            if let Ok((id, init_fn)) = self.get_func::<(), ()>(&name, self.init_export) {
-                self.set_current_id(id);
-                init_fn.call(&mut self.store, ())?;
+        for id in self.init_order() {
+            self.init_plug(id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Call the init functions of all plugins in the same order as [`Plugs::init`], but keep going after an init
+    /// export fails and return the errors of all plugins that failed
+    ///
+    /// The init exports of plugins that depend on a failed plugin aren't called, they fail with a [`DependencyFailed`]
+    /// error instead.
+    pub fn init_all(&mut self) -> Result<(), Vec<InitError>> {
+        let errors = self
+            .init_order()
+            .into_iter()
+            .filter_map(|id| self.init_plug(id).err())
+            .collect::<Vec<_>>();
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Returns the ids of all plugins with dependencies before their dependents, circular dependencies are
+    /// initialized in load order
+    fn init_order(&self) -> Vec<PlugId> {
+        fn visit<T>(
+            plugs: &Plugs<'_, T>,
+            id: PlugId,
+            visited: &mut [bool],
+            order: &mut Vec<PlugId>,
+        ) {
+            if visited[id] {
+                return;
+            }
+            visited[id] = true;
+            for dep in plugs.items[id].deps.iter() {
+                if let Some(&dep_id) = plugs.names.get(dep) {
+                    visit(plugs, dep_id, visited, order);
+                }
             }
+            order.push(id);
         }
 
+        let mut visited = vec![false; self.items.len()];
+        let mut order = Vec::with_capacity(self.items.len());
+        for id in 0..self.items.len() {
+            visit(self, id, &mut visited, &mut order);
+        }
+        order
+    }
+
+    /// Call the init export of a single plugin if it has one
+    fn init_plug(&mut self, id: PlugId) -> Result<(), InitError> {
+        let p = &self.items[id];
+        let failed_dep = p.deps.iter().find(|dep| {
+            self.get_plug(dep)
+                .is_some_and(|dep| matches!(dep.status, PlugStatus::Failed(_)))
+        });
+        let res = match (&p.status, failed_dep) {
+            (PlugStatus::Linked, Some(dep)) => Err(DependencyFailed {
+                name: p.name.clone(),
+                dep: dep.clone(),
+            }
+            .into()),
+            (PlugStatus::Linked, None) => self.call_init(id),
+            (PlugStatus::Initialized | PlugStatus::Disabled, _) => return Ok(()),
+            (status, _) => Err(InvalidPlugStatus {
+                name: p.name.clone(),
+                status: status.clone(),
+                expected: PlugStatus::Linked,
+            }
+            .into()),
+        };
+
+        let p = &mut self.items[id];
+        match res {
+            Ok(()) => {
+                // The plugin may have been disabled by its failure policy
+                if p.status == PlugStatus::Linked {
+                    p.status = PlugStatus::Initialized;
+                }
+                Ok(())
+            }
+            Err(error) => {
+                if p.status == PlugStatus::Linked {
+                    p.status = PlugStatus::Failed(error.to_string());
+                }
+                Err(InitError {
+                    plug_name: p.name.clone(),
+                    error,
+                })
+            }
+        }
+    }
+
+    fn call_init(&mut self, id: PlugId) -> wasmtime::Result<()> {
+        if let Ok(init_fn) = self.lookup_func::<(), ()>(id, self.init_export) {
+            let init_export = self.init_export;
+            self.run_call(id, init_export, |store| init_fn.call(store, ()))?;
+        }
         Ok(())
     }
 
@@ -490,16 +1541,27 @@ impl<'a, T> Plugs<'a, T> {
     ///
     /// # Errors
     ///
-    /// - May return [`UnknownPlugin::Name`] or other `wasmtime` errors via [`Plugs::get_func`].
+    /// - Returns [`UnknownPlugin::Name`] if the specified plugin couldn't be found.
+    /// - Returns [`PluginDisabled`] or [`InvalidPlugStatus`] if the plugin isn't initialized, before its exports are
+    ///   looked up.
+    /// - May return [`ExportNotFound`] or other `wasmtime` errors from [`wasmtime::Instance::get_typed_func`].
     pub fn call<P: WasmParams, R: WasmResults>(
         &mut self,
         plug: &str,
         func: &str,
         params: P,
     ) -> wasmtime::Result<R> {
-        let (id, f) = self.get_func(plug, func)?;
-        self.set_current_id(id);
-        f.call(&mut self.store, params)
+        let id = self
+            .get_id(plug)
+            .ok_or_else(|| UnknownPlugin::Name(plug.to_string()))?;
+        self.check_initialized(id)?;
+        let f = if self.interceptors.is_empty() {
+            self.lookup_func::<P, R>(id, func)?
+        } else {
+            self.intercepted_func(id, func)?
+                .typed::<P, R>(&self.store)?
+        };
+        self.run_call(id, func, |store| f.call(store, params))
     }
 
     /// Method for calling functions in plugins without knowing their type signature. The function returns a list of returns from the plugin function if such function could be found and if the arguements matched the functions type signature.
@@ -517,6 +1579,7 @@ impl<'a, T> Plugs<'a, T> {
         args: &[Val],
     ) -> wasmtime::Result<Vec<Val>> {
         if let Some(id) = self.get_id(plug) {
+            self.check_initialized(id)?;
             let p = self.items.get(id).unwrap();
             if !p.exports.contains(&func.to_string()) {
                 return Err(ExportNotFound {
@@ -535,8 +1598,8 @@ impl<'a, T> Plugs<'a, T> {
                 })?;
                 let ftype = f.ty(&mut self.store);
                 let mut arg_types = Vec::with_capacity(args.len());
-                for i in 0..args.len() {
-                    arg_types.push(args[i].ty(&mut self.store)?);
+                for arg in args {
+                    arg_types.push(arg.ty(&mut self.store)?);
                 }
 
                 if arg_types.len() != ftype.params().len() {
@@ -562,14 +1625,41 @@ impl<'a, T> Plugs<'a, T> {
                 }
 
                 let mut returns = vec![Val::I32(0); ftype.results().len()];
-                self.set_current_id(id);
-                f.call(&mut self.store, args, &mut returns)?;
+                if self.interceptors.is_empty() {
+                    self.run_call(id, func, |store| f.call(store, args, &mut returns))?;
+                    return Ok(returns);
+                }
+
+                let interceptors = self.interceptors.clone();
+                let callee = Callee::Plug {
+                    id,
+                    name: plug.to_string(),
+                    func: func.to_string(),
+                };
+                let info = CallInfo {
+                    caller: None,
+                    callee: &callee,
+                    args,
+                };
+                self.run_call(id, func, |store| {
+                    intercept::intercept(&interceptors, &info, &mut returns, |returns| {
+                        f.call(&mut *store, args, returns)
+                    })?;
+                    for (ret, ty) in returns.iter().zip(ftype.results()) {
+                        if !ret.ty(&*store)?.matches(&ty) {
+                            return Err(wasmtime::Error::msg(format!(
+                                "Interceptor of '{callee}' returned a value of the wrong type, expected {ty}"
+                            )));
+                        }
+                    }
+                    Ok(())
+                })?;
                 Ok(returns)
             } else {
-                return Err(wasmtime::Error::msg(format!(
+                Err(wasmtime::Error::msg(format!(
                     "Plugin '{}' hasn't been instantiated yet",
                     p.name
-                )));
+                )))
             }
         } else {
             Err(UnknownPlugin::Name(plug.to_string()).into())
@@ -586,12 +1676,23 @@ impl<'a, T> Plugs<'a, T> {
     /// # Errors
     ///
     /// - Returns [`UnknownPlugin::Id`] if a plugin with the requested id couldn't be found.
+    /// - Returns [`PluginDisabled`] or [`InvalidPlugStatus`] if the plugin isn't initialized.
     /// - Returns [`ExportNotFound`] if the requested function name couldn't be found inside the plugin.
     /// - May return `wasmtime` errors from [`wasmtime::Instance::get_typed_func`].
     pub fn get_func_by_id<P: WasmParams, R: WasmResults>(
         &mut self,
         plug_id: PlugId,
         func: &str,
+    ) -> wasmtime::Result<TypedFunc<P, R>> {
+        self.check_initialized(plug_id)?;
+        self.lookup_func(plug_id, func)
+    }
+
+    /// Look up a function like [`Plugs::get_func_by_id`] without checking the plugin's status
+    fn lookup_func<P: WasmParams, R: WasmResults>(
+        &mut self,
+        plug_id: PlugId,
+        func: &str,
     ) -> wasmtime::Result<TypedFunc<P, R>> {
         if let Some(p) = self.items.get(plug_id) {
             if !p.exports.contains(&func.to_string()) {
@@ -604,7 +1705,6 @@ impl<'a, T> Plugs<'a, T> {
             }
             if let Some(inst) = p.instance {
                 inst.get_typed_func::<P, R>(&mut self.store, func)
-                    .map(|f| f)
             } else {
                 Err(wasmtime::Error::msg(format!(
                     "Plugin '{}' hasn't been instantiated yet",
@@ -621,6 +1721,7 @@ impl<'a, T> Plugs<'a, T> {
     /// # Errors
     ///
     /// - Returns [`UnknownPlugin::Name`] if a plugin with the requested name couldn't be found.
+    /// - Returns [`PluginDisabled`] or [`InvalidPlugStatus`] if the plugin isn't initialized.
     /// - Returns [`ExportNotFound`] if the requested function name couldn't be found inside the plugin.
     /// - May return `wasmtime` errors from [`wasmtime::Instance::get_typed_func`].
     pub fn get_func<P: WasmParams, R: WasmResults>(
@@ -629,8 +1730,7 @@ impl<'a, T> Plugs<'a, T> {
         func: &str,
     ) -> wasmtime::Result<(PlugId, TypedFunc<P, R>)> {
         if let Some(&p_id) = self.names.get(plug) {
-            self.get_func_by_id::<P, R>(p_id, func)
-                .and_then(|f| Ok((p_id, f)))
+            self.get_func_by_id::<P, R>(p_id, func).map(|f| (p_id, f))
         } else {
             Err(UnknownPlugin::Name(plug.to_string()).into())
         }
@@ -643,17 +1743,17 @@ impl<'a, T> Plugs<'a, T> {
 
     /// Get name of plugin by id
     pub fn get_name(&self, id: PlugId) -> Option<&String> {
-        self.items.get(id).and_then(|p| Some(&p.name))
+        self.items.get(id).map(|p| &p.name)
     }
 
     /// Get reference to plugin by name
     pub fn get_plug(&self, name: &str) -> Option<&Plug<T>> {
-        self.get_id(name).and_then(|id| Some(&self.items[id]))
+        self.get_id(name).map(|id| &self.items[id])
     }
 
     /// Get mutable reference to plugin by name
     pub fn get_plug_mut(&mut self, name: &str) -> Option<&mut Plug<T>> {
-        self.get_id(name).and_then(|id| Some(&mut self.items[id]))
+        self.get_id(name).map(|id| &mut self.items[id])
     }
 
     /// Get reference to plugin by id
@@ -666,3 +1766,117 @@ impl<'a, T> Plugs<'a, T> {
         self.items.get_mut(id)
     }
 }
+
+/// Read the NUL-terminated UTF-8 string at `ptr` that was returned by the metadata export `export`
+fn read_metadata_str(memory: &[u8], export: &str, ptr: u32) -> Result<String, MetadataError> {
+    let bytes = memory
+        .get(ptr as usize..)
+        .ok_or_else(|| MetadataError::OutOfBounds {
+            export: export.to_string(),
+            ptr,
+        })?;
+    let len = bytes
+        .iter()
+        .take(MAX_METADATA_LEN + 1)
+        .position(|b| *b == 0)
+        .ok_or_else(|| MetadataError::Unterminated {
+            export: export.to_string(),
+        })?;
+    String::from_utf8(bytes[..len].to_vec()).map_err(|_| MetadataError::InvalidUtf8 {
+        export: export.to_string(),
+    })
+}
+
+/// Check that a plugin name returned by the metadata export `export` can be used in `__deps` lists
+fn validate_name(export: &str, name: &str) -> Result<(), MetadataError> {
+    if name.is_empty() || name.contains(';') || name.starts_with('?') {
+        return Err(MetadataError::InvalidName {
+            export: export.to_string(),
+            name: name.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Compile the module of a plugin file, with the `wat` feature `.wat` files are parsed as WebAssembly text format
+fn read_module(path: &Path, engine: &Engine) -> wasmtime::Result<Module> {
+    compile_module(&std::fs::read(path)?, path, engine)
+}
+
+/// Compile the contents of the plugin file at `path` (see [`read_module`]). `.wat` files are parsed here with the `wat`
+/// feature to report `WatError`s, otherwise `Module::new` parses text modules itself (see [`Plugs::load`]).
+#[cfg_attr(not(feature = "wat"), allow(unused_variables))]
+fn compile_module(bin: &[u8], path: &Path, engine: &Engine) -> wasmtime::Result<Module> {
+    #[cfg(feature = "wat")]
+    if path.extension().is_some_and(|ext| ext == "wat") {
+        let bin = wat::parse(std::str::from_utf8(bin)?, Some(path))?;
+        return Module::from_binary(engine, &bin);
+    }
+
+    Module::new(engine, bin)
+}
+
+/// Create a default valued global, an empty table or a new memory for the given type.
+/// Returns `None` for functions and types without a default value.
+fn placeholder<T>(
+    store: &mut Store<PlugContext<T>>,
+    ty: &ExternType,
+) -> wasmtime::Result<Option<Extern>> {
+    let placeholder: Extern = match ty {
+        ExternType::Func(_) => return Ok(None),
+        ExternType::Global(ty) => {
+            if let Some(val) = Val::default_for_ty(ty.content()) {
+                Global::new(&mut *store, ty.clone(), val)?.into()
+            } else {
+                return Ok(None);
+            }
+        }
+        ExternType::Table(ty) => {
+            let init = Ref::null(ty.element().heap_type());
+            Table::new(&mut *store, ty.clone(), init)?.into()
+        }
+        ExternType::Memory(ty) => Memory::new(&mut *store, ty.clone())?.into(),
+    };
+    Ok(Some(placeholder))
+}
+
+/// Create a stub for a weak import according to `policy`, non-function imports get a placeholder (see [`placeholder`]).
+fn weak_stub<T>(
+    store: &mut Store<PlugContext<T>>,
+    policy: WeakImportPolicy,
+    imp: &PlugImport,
+) -> wasmtime::Result<Extern> {
+    let ty = if let ExternType::Func(ty) = &imp.ty {
+        ty.clone()
+    } else {
+        return placeholder(store, &imp.ty)?.ok_or_else(|| {
+            wasmtime::Error::msg(format!("Weak import '{imp}' doesn't have a default value"))
+        });
+    };
+
+    let func = match policy {
+        WeakImportPolicy::Trap => {
+            let imp_name = imp.to_string();
+            Func::new(&mut *store, ty, move |_, _, _| {
+                Err(wasmtime::Error::msg(format!(
+                    "Weak import '{imp_name}' was called but its optional dependency isn't loaded"
+                )))
+            })
+        }
+        WeakImportPolicy::Zero => {
+            let mut defaults = Vec::with_capacity(ty.results().len());
+            for result in ty.results() {
+                defaults.push(Val::default_for_ty(&result).ok_or_else(|| {
+                    wasmtime::Error::msg(format!(
+                        "Weak import '{imp}' doesn't have a default value"
+                    ))
+                })?);
+            }
+            Func::new(&mut *store, ty, move |_, _, results| {
+                results.copy_from_slice(&defaults);
+                Ok(())
+            })
+        }
+    };
+    Ok(func.into())
+}
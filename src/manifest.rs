@@ -0,0 +1,339 @@
+use std::{
+    collections::{HashMap, HashSet},
+    path::{Path, PathBuf},
+};
+
+use serde::Deserialize;
+use wasmtime::{Engine, Module};
+
+use crate::{plan::is_granted, ConfigValue, ManifestError, PlugId, PlugMetadata, Plugs};
+
+/// A list of plugins to load in order together with their settings, see [`Plugs::load_manifest`]
+///
+/// ```toml
+/// [[plugin]]
+/// path = "plug1.wasm"
+/// version = "1.0.0"
+/// capabilities = ["env.print"]
+/// config = { greeting = "hello", retries = 3 }
+/// limits = { fuel = 100000 }
+///
+/// [[plugin]]
+/// path = "plug2.wasm"
+/// enabled = false
+/// ```
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Manifest {
+    /// Plugins in load order
+    #[serde(default, rename = "plugin")]
+    pub plugins: Vec<ManifestPlug>,
+}
+
+/// An entry of a [`Manifest`]
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestPlug {
+    /// Path of the plugin, relative paths are relative to the directory of the manifest. With the `archive` feature this
+    /// can also be a `.wlug` archive.
+    pub path: PathBuf,
+    /// Disabled plugins are skipped, defaults to `true`
+    #[serde(default = "enabled_by_default")]
+    pub enabled: bool,
+    /// Version that the plugin's version export must return
+    pub version: Option<String>,
+    /// Host functions the plugin is allowed to import, either a whole module (`"env"`) or a single function
+    /// (`"env.print"`). All host functions are allowed if this is missing. See [`crate::Plug::capabilities`].
+    pub capabilities: Option<Vec<String>>,
+    /// Configuration of the plugin, see [`Plugs::set_config`]
+    #[serde(default)]
+    pub config: HashMap<String, ConfigValue>,
+    #[serde(default)]
+    pub limits: ManifestLimits,
+}
+
+fn enabled_by_default() -> bool {
+    true
+}
+
+/// Resource limits of a [`ManifestPlug`]
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ManifestLimits {
+    /// See [`crate::Plug::fuel_limit`]
+    pub fuel: Option<u64>,
+}
+
+impl Manifest {
+    /// Parse a manifest from TOML, `path` is only used in errors
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`ManifestError`] if `text` isn't a valid manifest.
+    pub fn parse(text: &str, path: impl AsRef<Path>) -> Result<Self, ManifestError> {
+        toml::from_str(text).map_err(|e| ManifestError {
+            path: path.as_ref().to_path_buf(),
+            message: e.message().to_string(),
+        })
+    }
+
+    /// Read and parse a manifest file
+    ///
+    /// # Errors
+    ///
+    /// - May return [`std::io::Error`]s from reading the file.
+    /// - Returns [`ManifestError`] if the file isn't a valid manifest.
+    pub fn from_file(path: impl AsRef<Path>) -> wasmtime::Result<Self> {
+        let path = path.as_ref();
+        Ok(Self::parse(&std::fs::read_to_string(path)?, path)?)
+    }
+}
+
+/// Why a plugin of a manifest wasn't loaded
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The plugin has `enabled = false`
+    Disabled,
+    /// The plugin couldn't be loaded, contains the error message
+    LoadFailed(String),
+    /// The plugin's version isn't the expected one, `found` is `None` if the plugin doesn't have a version export
+    VersionMismatch {
+        expected: String,
+        found: Option<String>,
+    },
+    /// The plugin imports a host function that isn't in its capabilities
+    CapabilityNotGranted { import: String },
+    /// The (non-optional) dependency `dep` isn't loaded and isn't in the manifest, or it was skipped itself
+    MissingDependency { dep: String },
+    /// The plugin has a fuel limit but fuel consumption isn't enabled in the engine's config
+    FuelNotEnabled,
+}
+
+impl std::fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SkipReason::Disabled => write!(f, "Plugin is disabled"),
+            SkipReason::LoadFailed(e) => write!(f, "Couldn't load plugin: {e}"),
+            SkipReason::VersionMismatch {
+                expected,
+                found: Some(found),
+            } => write!(f, "Expected version '{expected}' but found '{found}'"),
+            SkipReason::VersionMismatch {
+                expected,
+                found: None,
+            } => write!(f, "Expected version '{expected}' but plugin has no version"),
+            SkipReason::CapabilityNotGranted { import } => {
+                write!(
+                    f,
+                    "Host function '{import}' isn't in the plugin's capabilities"
+                )
+            }
+            SkipReason::MissingDependency { dep } => {
+                write!(f, "Dependency '{dep}' is missing or was skipped")
+            }
+            SkipReason::FuelNotEnabled => {
+                write!(
+                    f,
+                    "Plugin has a fuel limit but the engine doesn't consume fuel"
+                )
+            }
+        }
+    }
+}
+
+/// A plugin of a manifest that wasn't loaded
+#[derive(Clone, Debug)]
+pub struct SkippedPlug {
+    /// Path of the plugin as it is written in the manifest
+    pub path: PathBuf,
+    pub reason: SkipReason,
+}
+
+/// The result of [`Plugs::load_manifest`]
+#[derive(Clone, Debug, Default)]
+pub struct ManifestReport {
+    /// Ids of the loaded plugins in load order
+    pub loaded: Vec<PlugId>,
+    pub skipped: Vec<SkippedPlug>,
+}
+
+impl<'a, T> Plugs<'a, T> {
+    /// Create a `Plugs` and load, link and initialize the plugins of a manifest (see [`Plugs::load_manifest`])
+    ///
+    /// Since host functions need to be added before linking, use [`Plugs::load_manifest`] if your plugins import any.
+    ///
+    /// # Errors
+    ///
+    /// - May return any error [`Plugs::load_manifest`] returns.
+    pub fn from_manifest(
+        path: impl AsRef<Path>,
+        engine: &Engine,
+        state: T,
+    ) -> wasmtime::Result<(Self, ManifestReport)> {
+        let mut plugs = Self::new(engine, state);
+        let report = plugs.load_manifest(path, engine)?;
+        Ok((plugs, report))
+    }
+
+    /// Load the plugins of a manifest file (see [`Manifest`]) in order, then link and initialize all plugins.
+    ///
+    /// Plugins that are disabled, can't be loaded, don't have the expected version or import host functions outside of
+    /// their capabilities are skipped and reported in the returned [`ManifestReport`]. Plugins that depend on a skipped
+    /// plugin (directly or indirectly) are skipped with [`SkipReason::MissingDependency`]. Plugins with a fuel limit are
+    /// skipped if fuel consumption isn't enabled in the engine's config. The configuration and limits of loaded plugins
+    /// are applied before linking.
+    ///
+    /// # Errors
+    ///
+    /// - May return [`std::io::Error`]s or [`ManifestError`] from [`Manifest::from_file`].
+    /// - May return any error [`Plugs::link`] and [`Plugs::init`] return.
+    pub fn load_manifest(
+        &mut self,
+        path: impl AsRef<Path>,
+        engine: &Engine,
+    ) -> wasmtime::Result<ManifestReport> {
+        let path = path.as_ref();
+        let manifest = Manifest::from_file(path)?;
+        let dir = path.parent().unwrap_or(Path::new(""));
+
+        // All plugins are checked before any of them is added, so that dependents of skipped plugins can be skipped too
+        let mut checked = Vec::new();
+        for entry in manifest.plugins.iter() {
+            let res = if entry.enabled {
+                self.check_manifest_plug(entry, &dir.join(&entry.path), engine)
+            } else {
+                Err(SkipReason::Disabled)
+            };
+            checked.push((entry, res));
+        }
+        self.skip_missing_deps(&mut checked);
+
+        let mut report = ManifestReport::default();
+        for (entry, res) in checked {
+            match res.and_then(|plug| self.add_manifest_plug(entry, plug, engine)) {
+                Ok(id) => report.loaded.push(id),
+                Err(reason) => report.skipped.push(SkippedPlug {
+                    path: entry.path.clone(),
+                    reason,
+                }),
+            }
+        }
+
+        self.link()?;
+        self.init()?;
+        Ok(report)
+    }
+
+    /// Read a single plugin of a manifest and check it against its entry
+    fn check_manifest_plug(
+        &mut self,
+        entry: &ManifestPlug,
+        path: &Path,
+        engine: &Engine,
+    ) -> Result<CheckedPlug, SkipReason> {
+        // The limit couldn't be enforced
+        if entry.limits.fuel.is_some() && self.store.get_fuel().is_err() {
+            return Err(SkipReason::FuelNotEnabled);
+        }
+        let load_failed = |e: wasmtime::Error| SkipReason::LoadFailed(e.to_string());
+        #[cfg(feature = "archive")]
+        let (module, archive) = if path.extension().is_some_and(|ext| ext == "wlug") {
+            let (module, contents) =
+                crate::archive::read_archive(path, engine).map_err(load_failed)?;
+            (module, Some(contents))
+        } else {
+            (crate::read_module(path, engine).map_err(load_failed)?, None)
+        };
+        #[cfg(not(feature = "archive"))]
+        let module = crate::read_module(path, engine).map_err(load_failed)?;
+        let metadata = self
+            .extract_metadata(engine, &module, self.items.len())
+            .map_err(load_failed)?;
+
+        if let Some(expected) = &entry.version {
+            if metadata.version.as_ref() != Some(expected) {
+                return Err(SkipReason::VersionMismatch {
+                    expected: expected.clone(),
+                    found: metadata.version,
+                });
+            }
+        }
+
+        if let Some(capabilities) = &entry.capabilities {
+            let not_granted = metadata
+                .imports
+                .iter()
+                .find(|imp| self.is_host_import(imp) && !is_granted(capabilities, imp));
+            if let Some(imp) = not_granted {
+                return Err(SkipReason::CapabilityNotGranted {
+                    import: imp.to_string(),
+                });
+            }
+        }
+
+        Ok(CheckedPlug {
+            module,
+            metadata,
+            #[cfg(feature = "archive")]
+            archive,
+        })
+    }
+
+    /// Skip the checked plugins whose dependencies are neither loaded already nor about to be loaded. This is repeated
+    /// until nothing changes, so that plugins that depend on skipped dependents are skipped as well.
+    fn skip_missing_deps(&self, checked: &mut [(&ManifestPlug, Result<CheckedPlug, SkipReason>)]) {
+        loop {
+            let available = self
+                .names
+                .keys()
+                .chain(
+                    checked
+                        .iter()
+                        .filter_map(|(_, res)| Some(&res.as_ref().ok()?.metadata.name)),
+                )
+                .cloned()
+                .collect::<HashSet<_>>();
+            let missing = checked.iter().enumerate().find_map(|(i, (_, res))| {
+                let metadata = &res.as_ref().ok()?.metadata;
+                let dep = metadata.deps.iter().find(|dep| {
+                    !metadata.optional_deps.contains(dep) && !available.contains(*dep)
+                })?;
+                Some((i, dep.clone()))
+            });
+            let Some((i, dep)) = missing else {
+                return;
+            };
+            checked[i].1 = Err(SkipReason::MissingDependency { dep });
+        }
+    }
+
+    /// Add a plugin of a manifest that passed its checks and apply the settings of its entry
+    fn add_manifest_plug(
+        &mut self,
+        entry: &ManifestPlug,
+        plug: CheckedPlug,
+        engine: &Engine,
+    ) -> Result<PlugId, SkipReason> {
+        let id = self
+            .add_plug(plug.module, plug.metadata, engine)
+            .map_err(|e| SkipReason::LoadFailed(e.to_string()))?;
+        #[cfg(feature = "archive")]
+        if let Some(contents) = plug.archive {
+            self.add_archive(id, contents);
+        }
+        // Values in the manifest take precedence over the defaults of an archive
+        self.config.merge(id, entry.config.clone());
+        self.items[id].fuel_limit = entry.limits.fuel;
+        // Enforced again when linking, e.g. in case host functions are added later
+        self.items[id].capabilities = entry.capabilities.clone();
+        Ok(id)
+    }
+}
+
+/// A plugin of a manifest that passed its checks but wasn't added yet
+struct CheckedPlug {
+    module: Module,
+    metadata: PlugMetadata,
+    #[cfg(feature = "archive")]
+    archive: Option<crate::archive::ArchiveContents>,
+}
@@ -0,0 +1,394 @@
+use wasmtime::{ExternType, FuncType};
+
+use crate::{
+    builtins, ExportType, LinkError, Plug, PlugId, PlugImport, Plugs, WeakImportPolicy,
+    BUILTIN_MODULE, HOST_PROVIDER,
+};
+
+/// Where an import of a plugin is resolved from
+#[derive(Clone, Debug)]
+pub enum ImportSource {
+    /// A host function added with [`Plugs::add_host_fn`] or [`Plugs::add_host_fn_in_mod`]
+    Host,
+
+    /// One of the functions in [`BUILTIN_MODULE`]
+    Builtin,
+
+    /// The export with the same name as the import in the dependency `dep`
+    Dep { dep: String, id: PlugId },
+
+    /// A default added with [`Plugs::add_weak_default`], used because the optional dependency `dep` isn't loaded
+    WeakDefault { dep: String },
+
+    /// A stub created according to the [`WeakImportPolicy`], used because the optional dependency `dep` isn't loaded
+    WeakStub { dep: String },
+}
+
+impl std::fmt::Display for ImportSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ImportSource::Host => write!(f, "host"),
+            ImportSource::Builtin => write!(f, "builtin"),
+            ImportSource::Dep { dep, .. } => write!(f, "dependency '{dep}'"),
+            ImportSource::WeakDefault { dep } => {
+                write!(f, "host default for missing optional dependency '{dep}'")
+            }
+            ImportSource::WeakStub { dep } => {
+                write!(f, "stub for missing optional dependency '{dep}'")
+            }
+        }
+    }
+}
+
+/// An import together with where it will be resolved from
+#[derive(Clone, Debug)]
+pub struct ResolvedImport {
+    pub import: PlugImport,
+    pub source: ImportSource,
+}
+
+/// The result of resolving the imports of a single plugin, see [`LinkPlan`]
+#[derive(Clone, Debug)]
+pub struct PlugLinkPlan {
+    pub id: PlugId,
+    pub name: String,
+    /// Imports that could be resolved, in the order they are declared in the plugin's module
+    pub imports: Vec<ResolvedImport>,
+    /// Loaded dependencies that don't provide any of the plugin's imports
+    pub unused_deps: Vec<String>,
+    /// Optional dependencies that aren't loaded
+    pub missing_optional_deps: Vec<String>,
+    /// Every error that would prevent this plugin from being linked
+    pub errors: Vec<LinkError>,
+}
+
+impl PlugLinkPlan {
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// The result of [`Plugs::plan_link`], contains a [`PlugLinkPlan`] for each plugin in load order
+#[derive(Clone, Debug)]
+pub struct LinkPlan {
+    pub plugs: Vec<PlugLinkPlan>,
+}
+
+impl LinkPlan {
+    /// Returns true if `Plugs::link` wouldn't report any [`LinkError`]s
+    pub fn is_ok(&self) -> bool {
+        self.plugs.iter().all(PlugLinkPlan::is_ok)
+    }
+
+    /// Returns all errors of all plugins in load order
+    pub fn errors(&self) -> impl Iterator<Item = &LinkError> {
+        self.plugs.iter().flat_map(|p| p.errors.iter())
+    }
+
+    /// Get the plan of a plugin by name
+    pub fn get(&self, name: &str) -> Option<&PlugLinkPlan> {
+        self.plugs.iter().find(|p| p.name == name)
+    }
+}
+
+impl<T> Plugs<'_, T> {
+    /// Perform all of the resolution logic of [`Plugs::link`] without instantiating anything and report where each
+    /// import comes from, which dependencies are unused and every error instead of just the first one.
+    pub fn plan_link(&self) -> LinkPlan {
+        LinkPlan {
+            plugs: (0..self.items.len()).map(|id| self.plan_plug(id)).collect(),
+        }
+    }
+
+    pub(crate) fn plan_plug(&self, p_id: PlugId) -> PlugLinkPlan {
+        let p = &self.items[p_id];
+        let mut plan = PlugLinkPlan {
+            id: p_id,
+            name: p.name.clone(),
+            imports: Vec::new(),
+            unused_deps: Vec::new(),
+            missing_optional_deps: Vec::new(),
+            errors: Vec::new(),
+        };
+
+        let mut dep_ids = Vec::with_capacity(p.deps.len());
+        let mut missing_deps = Vec::new();
+        for dep_name in p.deps.iter() {
+            if let Some(&p_dep_id) = self.names.get(dep_name) {
+                dep_ids.push((dep_name.as_str(), p_dep_id));
+            } else if p.optional_deps.contains(dep_name) {
+                plan.missing_optional_deps.push(dep_name.clone());
+            } else {
+                plan.errors
+                    .push(LinkError::DependencyNotFound(dep_name.clone()));
+                missing_deps.push(dep_name.as_str());
+            }
+        }
+
+        let mut unresolved_imports = Vec::new();
+        for imp in p.imports.iter() {
+            match self.resolve_import(p_id, imp, &dep_ids, &plan.missing_optional_deps) {
+                Ok(Some(source)) => plan.imports.push(ResolvedImport {
+                    import: imp.clone(),
+                    source,
+                }),
+                // Imports from a missing dependency were already reported with `DependencyNotFound`
+                Ok(None) if missing_deps.contains(&imp.module.as_str()) => {}
+                Ok(None) => unresolved_imports.push(imp.clone()),
+                Err(e) => plan.errors.push(e),
+            }
+        }
+
+        if !unresolved_imports.is_empty() {
+            plan.errors.push(LinkError::UnresolvedImports {
+                plug_name: p.name.clone(),
+                unresolved_imports,
+            });
+        }
+
+        if let Some(capabilities) = &p.capabilities {
+            for resolved in plan.imports.iter() {
+                if matches!(resolved.source, ImportSource::Host)
+                    && !is_granted(capabilities, &resolved.import)
+                {
+                    plan.errors.push(LinkError::CapabilityNotGranted {
+                        plug: p.name.clone(),
+                        import: resolved.import.clone(),
+                    });
+                }
+            }
+        }
+
+        // Compare function signatures before instantiating, wasmtime would only report an opaque instantiation error
+        for resolved in plan.imports.iter() {
+            if let Some(e) = self.check_signature(p, resolved) {
+                plan.errors.push(e);
+            }
+        }
+
+        for (dep_name, _) in dep_ids {
+            let used = plan.imports.iter().any(
+                |resolved| matches!(&resolved.source, ImportSource::Dep { dep, .. } if dep == dep_name),
+            );
+            if !used {
+                plan.unused_deps.push(dep_name.to_string());
+            }
+        }
+
+        plan
+    }
+
+    /// Resolve a single import, returns `Ok(None)` if nothing provides it
+    #[allow(clippy::result_large_err)]
+    fn resolve_import(
+        &self,
+        p_id: PlugId,
+        imp: &PlugImport,
+        dep_ids: &[(&str, PlugId)],
+        missing_optional_deps: &[String],
+    ) -> Result<Option<ImportSource>, LinkError> {
+        let p = &self.items[p_id];
+        if self.is_host_import(imp) {
+            return Ok(Some(ImportSource::Host));
+        }
+        if builtins::is_builtin(imp) {
+            return Ok(Some(ImportSource::Builtin));
+        }
+
+        // Imports from an optional dependency that isn't loaded are weak
+        if missing_optional_deps.contains(&imp.module) {
+            let dep = imp.module.clone();
+            let default = self
+                .weak_defaults
+                .iter()
+                .find(|(module, name, _)| *module == imp.module && *name == imp.name);
+            if let Some((_, _, func)) = default {
+                let found = ExportType::from(func.ty(&self.store));
+                if found != ExportType::from(&imp.ty) {
+                    return Err(LinkError::KindMismatch {
+                        plug: p.name.clone(),
+                        import: imp.clone(),
+                        provider: HOST_PROVIDER.to_string(),
+                        expected: ExportType::from(&imp.ty),
+                        found,
+                    });
+                }
+                return Ok(Some(ImportSource::WeakDefault { dep }));
+            }
+            if can_stub(self.weak_import_policy, &imp.ty) {
+                return Ok(Some(ImportSource::WeakStub { dep }));
+            }
+            return Ok(None);
+        }
+
+        // Imports from a module that is named after a dependency can only come from that dependency,
+        // other imports come from the first dependency (in `__deps` order) that exports the same name and kind.
+        let provider =
+            if let Some(&dep) = dep_ids.iter().find(|(dep_name, _)| *dep_name == imp.module) {
+                Some(dep)
+            } else {
+                dep_ids
+                    .iter()
+                    .find(|(_, p_dep_id)| {
+                        self.items[*p_dep_id]
+                            .module
+                            .get_export(&imp.name)
+                            .is_some_and(|ty| ExportType::from(ty) == ExportType::from(&imp.ty))
+                    })
+                    .copied()
+            };
+
+        let (dep_name, p_dep_id) = if let Some(provider) = provider {
+            provider
+        } else {
+            // Report a kind mismatch if something with the same name exists, e.g. a plugin imports a
+            // global while its dependency exports a function with the same name
+            let namesake = dep_ids
+                .iter()
+                .find_map(|(dep_name, p_dep_id)| {
+                    self.items[*p_dep_id]
+                        .module
+                        .get_export(&imp.name)
+                        .map(|ty| (dep_name.to_string(), ExportType::from(ty)))
+                })
+                .or_else(|| {
+                    self.host_fns
+                        .iter()
+                        .find(|(module, name, _)| *module == imp.module && *name == imp.name)
+                        .map(|(_, _, func)| {
+                            (
+                                HOST_PROVIDER.to_string(),
+                                ExportType::from(func.ty(&self.store)),
+                            )
+                        })
+                });
+            if let Some((provider, found)) = namesake {
+                return Err(LinkError::KindMismatch {
+                    plug: p.name.clone(),
+                    import: imp.clone(),
+                    provider,
+                    expected: ExportType::from(&imp.ty),
+                    found,
+                });
+            }
+            return Ok(None);
+        };
+
+        // Plugins are linked in load order, so dependencies have to be loaded before their dependents
+        //
+        // Circular dependencies are disallowed because we can't easily detect which _symbol_ depends on which, we only know which plugin
+        // depends on which symbols and that isn't really enough to properly resolve all cases. If we were to just use that info, there
+        // could be some edge case where the linker doesn't properly link everything especially if the dependency graph is very
+        // convoluted and the circular dependency is deep within the dependency tree.
+        if p_dep_id >= p_id {
+            return Err(LinkError::NotInstantiated {
+                dep_name: dep_name.to_string(),
+                plug_name: p.name.clone(),
+            });
+        }
+
+        let found = if let Some(ty) = self.items[p_dep_id].module.get_export(&imp.name) {
+            ExportType::from(ty)
+        } else {
+            return Err(LinkError::ExportNotFound {
+                dep_name: dep_name.to_string(),
+                export_name: imp.name.clone(),
+                plug_name: p.name.clone(),
+            });
+        };
+        if found != ExportType::from(&imp.ty) {
+            return Err(LinkError::KindMismatch {
+                plug: p.name.clone(),
+                import: imp.clone(),
+                provider: dep_name.to_string(),
+                expected: ExportType::from(&imp.ty),
+                found,
+            });
+        }
+
+        Ok(Some(ImportSource::Dep {
+            dep: dep_name.to_string(),
+            id: p_dep_id,
+        }))
+    }
+
+    /// Returns a [`LinkError::SignatureMismatch`] if the import is a function with a different type than its provider
+    fn check_signature(&self, p: &Plug<T>, resolved: &ResolvedImport) -> Option<LinkError> {
+        let imp = &resolved.import;
+        let expected = if let ExternType::Func(expected) = &imp.ty {
+            expected
+        } else {
+            return None;
+        };
+
+        let (provider, found): (String, FuncType) = match &resolved.source {
+            ImportSource::Host => {
+                let (_, _, func) = self
+                    .host_fns
+                    .iter()
+                    .find(|(module, name, _)| *module == imp.module && *name == imp.name)?;
+                (
+                    HOST_PROVIDER.to_string(),
+                    func.ty(&self.store).func()?.clone(),
+                )
+            }
+            ImportSource::Builtin => (
+                BUILTIN_MODULE.to_string(),
+                builtins::func_type(self.store.engine(), &imp.name)?,
+            ),
+            ImportSource::Dep { dep, id } => (
+                dep.clone(),
+                self.items[*id]
+                    .module
+                    .get_export(&imp.name)?
+                    .func()?
+                    .clone(),
+            ),
+            ImportSource::WeakDefault { .. } => {
+                let (_, _, func) = self
+                    .weak_defaults
+                    .iter()
+                    .find(|(module, name, _)| *module == imp.module && *name == imp.name)?;
+                (
+                    HOST_PROVIDER.to_string(),
+                    func.ty(&self.store).func()?.clone(),
+                )
+            }
+            // Stubs are created from the import's own type
+            ImportSource::WeakStub { .. } => return None,
+        };
+
+        if found.matches(expected) {
+            None
+        } else {
+            Some(LinkError::SignatureMismatch {
+                plug: p.name.clone(),
+                import: imp.clone(),
+                provider,
+                expected: expected.clone(),
+                found,
+            })
+        }
+    }
+}
+
+/// Returns whether `capabilities` contain the module or the module and name of an import, see [`Plug::capabilities`]
+pub(crate) fn is_granted(capabilities: &[String], imp: &PlugImport) -> bool {
+    capabilities
+        .iter()
+        .any(|cap| *cap == imp.module || *cap == imp.to_string())
+}
+
+/// Returns whether a stub can be created for a weak import of the given type (see `weak_stub`)
+fn can_stub(policy: WeakImportPolicy, ty: &ExternType) -> bool {
+    match ty {
+        ExternType::Func(ty) => match policy {
+            WeakImportPolicy::Trap => true,
+            WeakImportPolicy::Zero => ty
+                .results()
+                .all(|result| wasmtime::Val::default_for_ty(&result).is_some()),
+        },
+        ExternType::Global(ty) => wasmtime::Val::default_for_ty(ty.content()).is_some(),
+        ExternType::Table(_) | ExternType::Memory(_) => true,
+    }
+}
@@ -0,0 +1,200 @@
+use std::{
+    collections::HashMap,
+    fmt::Write,
+    sync::{Arc, Mutex, PoisonError},
+    time::{Duration, Instant},
+};
+
+use wasmtime::{Func, Store, Trap};
+
+use crate::PlugContext;
+
+/// Statistics about the calls to a single function
+#[derive(Clone, Debug, Default)]
+pub struct CallStats {
+    pub calls: u64,
+    /// Number of calls that trapped, errors returned by host functions or interceptors aren't counted
+    pub traps: u64,
+    pub total_time: Duration,
+    pub max_time: Duration,
+    /// Fuel consumed by the calls, always 0 for host functions and if fuel consumption isn't enabled in the engine
+    pub fuel_used: u64,
+}
+
+impl CallStats {
+    fn record(&mut self, elapsed: Duration, trapped: bool, fuel_used: u64) {
+        self.calls += 1;
+        if trapped {
+            self.traps += 1;
+        }
+        self.total_time += elapsed;
+        self.max_time = self.max_time.max(elapsed);
+        self.fuel_used += fuel_used;
+    }
+}
+
+/// Statistics of a single plugin
+#[derive(Clone, Debug, Default)]
+pub struct PlugStats {
+    /// Calls into the plugin's exports (through `call`, `call_dynamic` and `init`) by export name
+    pub funcs: HashMap<String, CallStats>,
+    /// Calls from the plugin to host functions by `module.name`
+    pub host_fns: HashMap<String, CallStats>,
+}
+
+/// Statistics of all plugins by plugin name, see [`crate::Plugs::with_stats`]
+#[derive(Clone, Debug, Default)]
+pub struct Stats {
+    pub plugs: HashMap<String, PlugStats>,
+}
+
+impl Stats {
+    /// Serialize the statistics in the Prometheus text exposition format
+    pub fn to_prometheus(&self) -> String {
+        let mut plugs = self.plugs.iter().collect::<Vec<_>>();
+        plugs.sort_by(|a, b| a.0.cmp(b.0));
+
+        let mut funcs = Vec::new();
+        let mut host_fns = Vec::new();
+        for (plug, plug_stats) in plugs {
+            for (rows, stats) in [
+                (&mut funcs, &plug_stats.funcs),
+                (&mut host_fns, &plug_stats.host_fns),
+            ] {
+                let mut stats = stats.iter().collect::<Vec<_>>();
+                stats.sort_by(|a, b| a.0.cmp(b.0));
+                for (func, stats) in stats {
+                    let labels = format!(
+                        "plug=\"{}\",func=\"{}\"",
+                        escape_label(plug),
+                        escape_label(func)
+                    );
+                    rows.push((labels, stats));
+                }
+            }
+        }
+
+        let mut out = String::new();
+        write_metrics(&mut out, "wlug_calls", "plugin function", &funcs, true);
+        write_metrics(
+            &mut out,
+            "wlug_host_calls",
+            "host function",
+            &host_fns,
+            false,
+        );
+        out
+    }
+}
+
+/// Write the metrics of one kind of call, fuel is only measured for calls into plugins
+fn write_metrics(
+    out: &mut String,
+    prefix: &str,
+    kind: &str,
+    rows: &[(String, &CallStats)],
+    fuel: bool,
+) {
+    let mut metric = |suffix: &str, ty: &str, help: &str, value: fn(&CallStats) -> String| {
+        let _ = writeln!(out, "# HELP {prefix}{suffix} {help} to a {kind}");
+        let _ = writeln!(out, "# TYPE {prefix}{suffix} {ty}");
+        for (labels, stats) in rows {
+            let _ = writeln!(out, "{prefix}{suffix}{{{labels}}} {}", value(stats));
+        }
+    };
+
+    metric("_total", "counter", "Number of calls", |s| {
+        s.calls.to_string()
+    });
+    metric(
+        "_traps_total",
+        "counter",
+        "Number of calls that trapped",
+        |s| s.traps.to_string(),
+    );
+    metric("_seconds_total", "counter", "Time spent in calls", |s| {
+        s.total_time.as_secs_f64().to_string()
+    });
+    metric(
+        "_seconds_max",
+        "gauge",
+        "Duration of the longest call",
+        |s| s.max_time.as_secs_f64().to_string(),
+    );
+    if fuel {
+        metric("_fuel_total", "counter", "Fuel consumed by calls", |s| {
+            s.fuel_used.to_string()
+        });
+    }
+}
+
+fn escape_label(s: &str) -> String {
+    s.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Shared handle to the [`Stats`] of a `Plugs`, host function wrappers keep a clone of it
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StatsRecorder(Arc<Mutex<Stats>>);
+
+impl StatsRecorder {
+    pub(crate) fn snapshot(&self) -> Stats {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    pub(crate) fn clear(&self) {
+        *self.0.lock().unwrap_or_else(PoisonError::into_inner) = Stats::default();
+    }
+
+    pub(crate) fn record_call(
+        &self,
+        plug: &str,
+        func: &str,
+        elapsed: Duration,
+        trapped: bool,
+        fuel_used: u64,
+    ) {
+        let mut stats = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let plug_stats = stats.plugs.entry(plug.to_string()).or_default();
+        plug_stats
+            .funcs
+            .entry(func.to_string())
+            .or_default()
+            .record(elapsed, trapped, fuel_used);
+    }
+
+    fn record_host_call(&self, plug: &str, func: &str, elapsed: Duration, trapped: bool) {
+        let mut stats = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        let plug_stats = stats.plugs.entry(plug.to_string()).or_default();
+        plug_stats
+            .host_fns
+            .entry(func.to_string())
+            .or_default()
+            .record(elapsed, trapped, 0);
+    }
+
+    /// Wrap a host function so that calls to it from the plugin named `plug` are recorded as `func`
+    pub(crate) fn wrap_host_fn<T>(
+        &self,
+        store: &mut Store<PlugContext<T>>,
+        plug: &str,
+        func: &str,
+        host_fn: Func,
+    ) -> Func {
+        let recorder = self.clone();
+        let plug = plug.to_string();
+        let func = func.to_string();
+        let ty = host_fn.ty(&*store);
+        Func::new(&mut *store, ty, move |mut caller, args, results| {
+            let start = Instant::now();
+            let res = host_fn.call(&mut caller, args, results);
+            let trapped = res.as_ref().is_err_and(|e| e.is::<Trap>());
+            recorder.record_host_call(&plug, &func, start.elapsed(), trapped);
+            res
+        })
+    }
+}
@@ -0,0 +1,216 @@
+//! Helpers for testing hosts and plugins
+//!
+//! - [`FakePlug`] loads a plugin whose exports are implemented by Rust closures, useful for standing in for
+//!   the dependencies of the plugin under test.
+//! - [`CallRecorder`] is an [`Interceptor`] that records calls so they can be asserted on later.
+//! - [`assert_exports`] checks that a plugin exports a set of names.
+
+use std::{
+    fmt::Write,
+    sync::{Arc, Mutex, PoisonError},
+};
+
+use wasmtime::{Caller, Engine, Extern, Func, FuncType, IntoFunc, Module, Val};
+
+use crate::{CallInfo, Callee, Interceptor, Plug, PlugContext, PlugId, Plugs};
+
+/// Prefix of the import modules that the exports of fake plugins are imported from
+pub const FAKE_MODULE_PREFIX: &str = "wlug-fake:";
+
+/// Builder for a plugin whose function exports are implemented by the host.
+///
+/// The plugin is generated as a module that imports each function from `wlug-fake:<name>` and re-exports it, the
+/// functions are added to the `Plugs` as host functions in that module. Fake plugins also export a `memory` and a
+/// name export, they don't have dependencies.
+///
+/// ```no_run
+/// # use wlug::{Plugs, testing::FakePlug, wasmtime::Engine};
+/// # let engine = Engine::default();
+/// let mut plugs = Plugs::new(&engine, ());
+/// FakePlug::new(&mut plugs, "math")
+///     .func("add", |a: i32, b: i32| a + b)
+///     .load(&engine)
+///     .unwrap();
+/// ```
+pub struct FakePlug<'p, 'a, T> {
+    plugs: &'p mut Plugs<'a, T>,
+    name: String,
+    funcs: Vec<(String, Func)>,
+}
+
+impl<'p, 'a, T> FakePlug<'p, 'a, T> {
+    /// Start building a fake plugin named `name` that will be loaded into `plugs`
+    pub fn new(plugs: &'p mut Plugs<'a, T>, name: &str) -> Self {
+        Self {
+            plugs,
+            name: name.to_string(),
+            funcs: Vec::new(),
+        }
+    }
+
+    /// Add a function export, function parameters and results are passed through the generic types
+    pub fn func<Params, Results>(
+        mut self,
+        name: &str,
+        func: impl IntoFunc<PlugContext<T>, Params, Results>,
+    ) -> Self {
+        let func = Func::wrap(&mut self.plugs.store, func);
+        self.funcs.push((name.to_string(), func));
+        self
+    }
+
+    /// Add a function export with a signature that is only known at runtime
+    pub fn dynamic_func(
+        mut self,
+        name: &str,
+        ty: FuncType,
+        func: impl Fn(Caller<'_, PlugContext<T>>, &[Val], &mut [Val]) -> wasmtime::Result<()>
+            + Send
+            + Sync
+            + 'static,
+    ) -> Self {
+        let func = Func::new(&mut self.plugs.store, ty, func);
+        self.funcs.push((name.to_string(), func));
+        self
+    }
+
+    /// Load the fake plugin and return its id (see [`Plugs::load_module`])
+    ///
+    /// # Errors
+    ///
+    /// - May return any error [`Plugs::load_module`] returns.
+    pub fn load(self, engine: &Engine) -> wasmtime::Result<PlugId> {
+        let module_name = format!("{FAKE_MODULE_PREFIX}{}", self.name);
+
+        let mut wat = String::from("(module\n");
+        for (name, func) in self.funcs.iter() {
+            let ty = func.ty(&self.plugs.store);
+            let _ = write!(
+                wat,
+                "  (import {} {} (func",
+                wat_str(module_name.as_bytes()),
+                wat_str(name.as_bytes())
+            );
+            for param in ty.params() {
+                let _ = write!(wat, " (param {param})");
+            }
+            for result in ty.results() {
+                let _ = write!(wat, " (result {result})");
+            }
+            wat.push_str("))\n");
+        }
+        for (idx, (name, _)) in self.funcs.iter().enumerate() {
+            let _ = writeln!(wat, "  (export {} (func {idx}))", wat_str(name.as_bytes()));
+        }
+
+        let mut name = self.name.clone().into_bytes();
+        name.push(0);
+        let _ = writeln!(wat, "  (memory (export \"memory\") 1)");
+        let _ = writeln!(wat, "  (data (i32.const 0) {})", wat_str(&name));
+        let _ = writeln!(
+            wat,
+            "  (func (export {}) (result i32) i32.const 0)",
+            wat_str(self.plugs.name_export.as_bytes())
+        );
+        wat.push_str(")\n");
+
+        let module = Module::new(engine, wat)?;
+        for (name, func) in self.funcs {
+            self.plugs
+                .host_fns
+                .push((module_name.clone(), name, Extern::Func(func)));
+        }
+        self.plugs.load_module(module, engine)
+    }
+}
+
+/// Quote a WAT string, every byte is escaped so any name can be used
+fn wat_str(bytes: &[u8]) -> String {
+    let mut res = String::with_capacity(bytes.len() * 3 + 2);
+    res.push('"');
+    for b in bytes {
+        let _ = write!(res, "\\{b:02x}");
+    }
+    res.push('"');
+    res
+}
+
+/// A call that was recorded by a [`CallRecorder`]
+#[derive(Clone, Debug)]
+pub struct RecordedCall {
+    pub caller: Option<PlugId>,
+    pub callee: Callee,
+    pub args: Vec<Val>,
+    /// `None` if the call failed
+    pub results: Option<Vec<Val>>,
+}
+
+/// An [`Interceptor`] that records every call it sees in the order the calls returned.
+///
+/// `CallRecorder` is a handle that can be cloned, add a clone to the `Plugs` with [`Plugs::add_interceptor`] before
+/// linking and keep the original to inspect the calls.
+#[derive(Clone, Debug, Default)]
+pub struct CallRecorder(Arc<Mutex<Vec<RecordedCall>>>);
+
+impl CallRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns all recorded calls
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone()
+    }
+
+    /// Returns the recorded calls to `callee`, which is either `module.name` for host functions or `plugin.function`
+    pub fn calls_to(&self, callee: &str) -> Vec<RecordedCall> {
+        self.calls()
+            .into_iter()
+            .filter(|call| call.callee.to_string() == callee)
+            .collect()
+    }
+
+    /// Forget all recorded calls
+    pub fn clear(&self) {
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clear();
+    }
+}
+
+impl Interceptor for CallRecorder {
+    fn after(&self, call: &CallInfo<'_>, results: Result<&[Val], &wasmtime::Error>) {
+        let call = RecordedCall {
+            caller: call.caller,
+            callee: call.callee.clone(),
+            args: call.args.to_vec(),
+            results: results.ok().map(<[Val]>::to_vec),
+        };
+        self.0
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push(call);
+    }
+}
+
+/// Assert that `plug` has an export for each of the given names
+///
+/// # Panics
+///
+/// Panics with the list of missing exports if any of them are missing.
+#[track_caller]
+pub fn assert_exports<T>(plug: &Plug<T>, exports: &[&str]) {
+    let missing = exports
+        .iter()
+        .filter(|name| plug.module.get_export(name).is_none())
+        .collect::<Vec<_>>();
+    assert!(
+        missing.is_empty(),
+        "Plugin '{}' is missing the exports {missing:?}",
+        plug.name
+    );
+}
@@ -0,0 +1,226 @@
+use std::path::Path;
+
+use ed25519_dalek::{Signature, VerifyingKey};
+use sha2::{Digest, Sha256};
+use wasmtime::Engine;
+
+use crate::{PlugId, Plugs, VerificationError};
+
+/// Name of the custom section that contains the ed25519 signature of a module
+pub const SIGNATURE_SECTION: &str = "wlug.signature";
+
+/// How a plugin binary is verified before it is compiled, see [`Plugs::load_verified`]
+#[derive(Clone, Debug)]
+pub enum Verification {
+    /// The binary must have this SHA-256 digest
+    Sha256([u8; 32]),
+    /// The binary must be signed by one of the keys added with [`Plugs::with_trusted_key`]. The signature is read from a
+    /// detached `<file>.sig` file next to the plugin if there is one, otherwise from the [`SIGNATURE_SECTION`] custom
+    /// section.
+    Signature,
+    /// Like [`Verification::Signature`] but with a detached signature that is passed directly
+    DetachedSignature([u8; 64]),
+}
+
+/// Returns the SHA-256 digest of `bin`
+pub fn sha256(bin: &[u8]) -> [u8; 32] {
+    Sha256::digest(bin).into()
+}
+
+/// Returns `bin` without its [`SIGNATURE_SECTION`] together with the contents of that section.
+///
+/// Signatures are always computed over the module without its signature section, so a signed module can be created by
+/// signing the unsigned module and appending the section with [`embed_signature`].
+///
+/// # Errors
+///
+/// - Returns [`VerificationError::MalformedModule`] if `bin` isn't a WebAssembly binary or if it has more than one
+///   signature section.
+pub fn strip_signature(bin: &[u8]) -> Result<(Vec<u8>, Option<Vec<u8>>), VerificationError> {
+    if bin.len() < 8 || &bin[..4] != b"\0asm" {
+        return Err(VerificationError::MalformedModule);
+    }
+
+    let mut stripped = bin[..8].to_vec();
+    let mut signature = None;
+    let mut pos = 8;
+    while pos < bin.len() {
+        let start = pos;
+        let id = bin[pos];
+        pos += 1;
+        let size = read_u32(bin, &mut pos).ok_or(VerificationError::MalformedModule)?;
+        let end = pos
+            .checked_add(size as usize)
+            .filter(|end| *end <= bin.len())
+            .ok_or(VerificationError::MalformedModule)?;
+
+        if id == 0 {
+            let mut name_pos = pos;
+            let name_len =
+                read_u32(bin, &mut name_pos).ok_or(VerificationError::MalformedModule)?;
+            let name_end = name_pos
+                .checked_add(name_len as usize)
+                .filter(|name_end| *name_end <= end)
+                .ok_or(VerificationError::MalformedModule)?;
+            if &bin[name_pos..name_end] == SIGNATURE_SECTION.as_bytes() {
+                if signature.replace(bin[name_end..end].to_vec()).is_some() {
+                    return Err(VerificationError::MalformedModule);
+                }
+                pos = end;
+                continue;
+            }
+        }
+
+        stripped.extend_from_slice(&bin[start..end]);
+        pos = end;
+    }
+
+    Ok((stripped, signature))
+}
+
+/// Returns `bin` with its signature section replaced by one that contains `signature`
+///
+/// # Errors
+///
+/// - Returns [`VerificationError::MalformedModule`] if `bin` isn't a WebAssembly binary.
+pub fn embed_signature(bin: &[u8], signature: &[u8; 64]) -> Result<Vec<u8>, VerificationError> {
+    let (mut res, _) = strip_signature(bin)?;
+    let mut content = Vec::with_capacity(SIGNATURE_SECTION.len() + 65);
+    write_u32(&mut content, SIGNATURE_SECTION.len() as u32);
+    content.extend_from_slice(SIGNATURE_SECTION.as_bytes());
+    content.extend_from_slice(signature);
+
+    res.push(0);
+    write_u32(&mut res, content.len() as u32);
+    res.extend_from_slice(&content);
+    Ok(res)
+}
+
+/// Verify `bin` according to `verification`, `detached` is a signature read from a `.sig` file
+fn verify(
+    bin: &[u8],
+    verification: &Verification,
+    detached: Option<Vec<u8>>,
+    trusted_keys: &[[u8; 32]],
+) -> Result<(), VerificationError> {
+    let signature = match verification {
+        Verification::Sha256(expected) => {
+            let found = sha256(bin);
+            if found != *expected {
+                return Err(VerificationError::DigestMismatch {
+                    expected: *expected,
+                    found,
+                });
+            }
+            return Ok(());
+        }
+        Verification::Signature => detached,
+        Verification::DetachedSignature(signature) => Some(signature.to_vec()),
+    };
+
+    let (message, embedded) = strip_signature(bin)?;
+    let signature = signature
+        .or(embedded)
+        .ok_or(VerificationError::MissingSignature)?;
+    let signature = <[u8; 64]>::try_from(signature.as_slice())
+        .map_err(|_| VerificationError::MalformedSignature)?;
+    let signature = Signature::from_bytes(&signature);
+
+    // Keys that aren't valid ed25519 public keys can't have signed anything
+    let trusted = trusted_keys.iter().any(|key| {
+        VerifyingKey::from_bytes(key)
+            .is_ok_and(|key| key.verify_strict(&message, &signature).is_ok())
+    });
+    if trusted {
+        Ok(())
+    } else {
+        Err(VerificationError::UntrustedSignature)
+    }
+}
+
+/// Read an unsigned LEB128 encoded `u32`
+fn read_u32(bin: &[u8], pos: &mut usize) -> Option<u32> {
+    let mut res = 0u32;
+    for shift in (0..35).step_by(7) {
+        let byte = *bin.get(*pos)?;
+        *pos += 1;
+        res |= u32::from(byte & 0x7f).checked_shl(shift)?;
+        if byte & 0x80 == 0 {
+            return Some(res);
+        }
+    }
+    None
+}
+
+/// Write an unsigned LEB128 encoded `u32`
+fn write_u32(out: &mut Vec<u8>, mut value: u32) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+impl<'a, T> Plugs<'a, T> {
+    /// Trust signatures made with the given ed25519 public key, see [`Verification::Signature`]
+    pub fn with_trusted_key(mut self, key: [u8; 32]) -> Self {
+        self.trusted_keys.push(key);
+        self
+    }
+
+    /// Verify the provided binary and load it (see `load_binary`)
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`VerificationError`] if the binary couldn't be verified, nothing is compiled in that case.
+    /// - May return any error [`Plugs::load_binary`] returns.
+    pub fn load_binary_verified(
+        &mut self,
+        bin: impl AsRef<[u8]>,
+        verification: &Verification,
+        engine: &Engine,
+    ) -> wasmtime::Result<PlugId> {
+        verify(bin.as_ref(), verification, None, &self.trusted_keys)?;
+
+        self.load_binary(bin, engine)
+    }
+
+    /// Verify a plugin file and load it (see `load`)
+    ///
+    /// With [`Verification::Signature`], a detached signature is read from the file with the same path and an additional
+    /// `.sig` extension (e.g. `plug1.wasm.sig`) if it exists. `.sig` files contain the 64 bytes of the signature.
+    ///
+    /// # Errors
+    ///
+    /// - Returns [`VerificationError`] if the file couldn't be verified, nothing is compiled in that case.
+    /// - May return [`std::io::Error`]s from reading the file or its signature.
+    /// - May return any error [`Plugs::load`] returns.
+    pub fn load_verified(
+        &mut self,
+        file_path: impl AsRef<Path>,
+        verification: &Verification,
+        engine: &Engine,
+    ) -> wasmtime::Result<PlugId> {
+        let path = file_path.as_ref();
+        let bin = std::fs::read(path)?;
+
+        let mut detached = None;
+        if let Verification::Signature = verification {
+            let mut sig_path = path.as_os_str().to_owned();
+            sig_path.push(".sig");
+            match std::fs::read(sig_path) {
+                Ok(sig) => detached = Some(sig),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        verify(&bin, verification, detached, &self.trusted_keys)?;
+
+        let module = crate::compile_module(&bin, path, engine)?;
+        self.load_module(module, engine)
+    }
+}
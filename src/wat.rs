@@ -0,0 +1,22 @@
+use std::path::Path;
+
+use wast::parser::{self, ParseBuffer};
+
+use crate::WatError;
+
+/// Parse a module in the WebAssembly text format into its binary form
+pub(crate) fn parse(text: &str, path: Option<&Path>) -> Result<Vec<u8>, WatError> {
+    let to_error = |e: wast::Error| {
+        let (line, column) = e.span().linecol_in(text);
+        WatError {
+            path: path.map(Path::to_path_buf),
+            line: line + 1,
+            column: column + 1,
+            message: e.message(),
+        }
+    };
+
+    let buf = ParseBuffer::new(text).map_err(to_error)?;
+    let mut wat = parser::parse::<wast::Wat>(&buf).map_err(to_error)?;
+    wat.encode().map_err(to_error)
+}
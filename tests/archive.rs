@@ -0,0 +1,199 @@
+#![cfg(all(feature = "archive", feature = "wat"))]
+
+mod common;
+
+use std::{io::Write, path::PathBuf};
+
+use wlug::{wasmtime::Engine, ArchiveError, ConfigValue, Plugs, MAX_ARCHIVE_ENTRY_SIZE};
+
+const MANIFEST: &str = r#"
+module = "plugin.wat"
+
+[metadata]
+description = "Reads its assets"
+
+[config]
+level = 4
+"#;
+
+/// Write a `.wlug` archive with the given files into a temporary directory
+fn archive(name: &str, files: &[(&str, &[u8])]) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("archive");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(format!("{name}.wlug"));
+
+    let mut builder = tar::Builder::new(std::fs::File::create(&path).unwrap());
+    for (file, data) in files {
+        let mut header = tar::Header::new_gnu();
+        header.set_size(data.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        builder.append_data(&mut header, file, *data).unwrap();
+    }
+    builder.finish().unwrap();
+    path
+}
+
+fn assets_archive(name: &str) -> PathBuf {
+    let module = std::fs::read(common::fixture("assets")).unwrap();
+    archive(
+        name,
+        &[
+            ("wlug.toml", MANIFEST.as_bytes()),
+            ("plugin.wat", &module),
+            ("assets/greeting.txt", b"hello archive"),
+            ("notes.txt", b"not an asset"),
+        ],
+    )
+}
+
+fn read_memory(plugs: &mut Plugs<'_, common::Log>, len: usize) -> Vec<u8> {
+    let instance = plugs.get_plug("assets").unwrap().instance.unwrap();
+    let memory = instance.get_memory(&mut plugs.store, "memory").unwrap();
+    memory.data(&plugs.store)[128..128 + len].to_vec()
+}
+
+#[test]
+fn plugins_read_their_assets() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    plugs.load_archive(assets_archive("read"), &engine).unwrap();
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    assert_eq!(
+        plugs
+            .call::<(), i32>("assets", "greeting_size", ())
+            .unwrap(),
+        13
+    );
+    assert_eq!(
+        plugs.call::<(), i32>("assets", "missing_size", ()).unwrap(),
+        -1
+    );
+
+    assert_eq!(
+        plugs
+            .call::<(i32, i32), i32>("assets", "read_greeting", (0, 5))
+            .unwrap(),
+        5
+    );
+    assert_eq!(read_memory(&mut plugs, 5), b"hello");
+    // Reads are cut off at the end of the asset
+    assert_eq!(
+        plugs
+            .call::<(i32, i32), i32>("assets", "read_greeting", (6, 100))
+            .unwrap(),
+        7
+    );
+    assert_eq!(read_memory(&mut plugs, 7), b"archive");
+    assert_eq!(
+        plugs
+            .call::<(i32, i32), i32>("assets", "read_greeting", (20, 100))
+            .unwrap(),
+        0
+    );
+}
+
+#[test]
+fn archive_manifest_is_applied() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    plugs.load(assets_archive("manifest"), &engine).unwrap();
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    assert_eq!(plugs.call::<(), i64>("assets", "level", ()).unwrap(), 4);
+    assert_eq!(
+        plugs.get_config("assets", "level"),
+        Some(ConfigValue::Int(4))
+    );
+
+    let archive = plugs.archive("assets").unwrap();
+    assert_eq!(archive.assets, ["greeting.txt"]);
+    assert_eq!(archive.manifest.module, "plugin.wat");
+    assert_eq!(archive.manifest.metadata["description"], "Reads its assets");
+}
+
+#[test]
+fn assets_are_scoped_to_archives() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    plugs.load(common::fixture("assets"), &engine).unwrap();
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    assert!(plugs.archive("assets").is_none());
+    assert_eq!(
+        plugs
+            .call::<(), i32>("assets", "greeting_size", ())
+            .unwrap(),
+        -1
+    );
+    assert_eq!(plugs.call::<(), i64>("assets", "level", ()).unwrap(), 0);
+}
+
+#[test]
+fn archives_without_a_module_are_rejected() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    let path = archive("empty", &[("assets/greeting.txt", b"hello")]);
+    let err = plugs.load_archive(&path, &engine).unwrap_err();
+    let err = err.downcast_ref::<ArchiveError>().unwrap();
+    assert_eq!(err.path(), path);
+    assert_eq!(err.entry(), Some("plugin.wasm"));
+}
+
+#[test]
+fn archive_entry_sizes_are_limited() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+
+    // A header that claims a huge entry without the data to back it up, written over an empty archive
+    let path = archive("huge", &[]);
+    let mut header = tar::Header::new_gnu();
+    header.set_path("plugin.wasm").unwrap();
+    header.set_size(u64::MAX / 2);
+    header.set_mode(0o644);
+    header.set_cksum();
+    let mut file = std::fs::File::create(&path).unwrap();
+    file.write_all(header.as_bytes()).unwrap();
+    file.write_all(&[0; 1024]).unwrap();
+    drop(file);
+
+    let err = plugs.load_archive(&path, &engine).unwrap_err();
+    let err = err.downcast_ref::<ArchiveError>().unwrap();
+    assert!(matches!(
+        err,
+        ArchiveError::EntryTooLarge {
+            limit: MAX_ARCHIVE_ENTRY_SIZE,
+            ..
+        }
+    ));
+    assert_eq!(err.path(), path);
+    assert_eq!(err.entry(), Some("plugin.wasm"));
+}
+
+#[test]
+fn manifests_load_archives() {
+    let engine = Engine::default();
+    let path = assets_archive("listed");
+    let manifest = path.with_file_name("listed.toml");
+    std::fs::write(
+        &manifest,
+        "[[plugin]]\npath = \"listed.wlug\"\nconfig = { level = 7 }\n",
+    )
+    .unwrap();
+
+    let mut plugs = common::plugs(&engine);
+    let report = plugs.load_manifest(&manifest, &engine).unwrap();
+    assert!(report.skipped.is_empty());
+    assert_eq!(
+        plugs
+            .call::<(), i32>("assets", "greeting_size", ())
+            .unwrap(),
+        13
+    );
+    // The manifest overrides the default configuration of the archive
+    assert_eq!(plugs.call::<(), i64>("assets", "level", ()).unwrap(), 7);
+}
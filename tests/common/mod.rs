@@ -0,0 +1,39 @@
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use wlug::{
+    wasmtime::{Caller, Engine},
+    PlugContext, PlugId, Plugs,
+};
+
+/// State of the `Plugs` used in tests, collects the values passed to the `env.log` host function
+pub type Log = Vec<i32>;
+
+pub fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("fixtures")
+        .join(format!("{name}.wat"))
+}
+
+/// Create a `Plugs` with the `env.log` host function that the fixtures import
+pub fn plugs(engine: &Engine) -> Plugs<'static, Log> {
+    let mut plugs = Plugs::new(engine, Log::new());
+    plugs.add_host_fn("log", |mut caller: Caller<'_, PlugContext<Log>>, x: i32| {
+        caller.data_mut().1.push(x);
+    });
+    plugs
+}
+
+/// Load the given fixtures in order
+pub fn load(plugs: &mut Plugs<'_, Log>, engine: &Engine, names: &[&str]) -> Vec<PlugId> {
+    names
+        .iter()
+        .map(|name| {
+            plugs
+                .load(fixture(name), engine)
+                .unwrap_or_else(|e| panic!("couldn't load fixture '{name}': {e}"))
+        })
+        .collect()
+}
@@ -0,0 +1,161 @@
+mod common;
+
+use wlug::{testing::CallRecorder, wasmtime::Engine, ConfigValue, UnknownPlugin};
+
+fn read_greeting(plugs: &mut wlug::Plugs<'_, common::Log>, len: usize) -> String {
+    let memory = plugs.get_plug("configured").unwrap().instance.unwrap();
+    let memory = memory.get_memory(&mut plugs.store, "memory").unwrap();
+    String::from_utf8(memory.data(&plugs.store)[128..128 + len].to_vec()).unwrap()
+}
+
+#[test]
+fn builtins_read_memory_with_interceptors() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["configured"]);
+    let recorder = CallRecorder::new();
+    plugs.add_interceptor(recorder.clone());
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    plugs
+        .set_config(
+            "configured",
+            [("greeting", "hello".into()), ("retries", 3.into())],
+        )
+        .unwrap();
+    assert_eq!(
+        plugs.call::<(), i64>("configured", "retries", ()).unwrap(),
+        3
+    );
+    assert_eq!(
+        plugs
+            .call::<i32, i32>("configured", "greeting", 64)
+            .unwrap(),
+        5
+    );
+    assert_eq!(read_greeting(&mut plugs, 5), "hello");
+
+    let calls = recorder.calls_to("wlug.config_get_int");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].results.as_ref().unwrap()[0].unwrap_i64(), 3);
+}
+
+#[test]
+fn plugins_read_their_config() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["configured"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    // Nothing is configured yet so the defaults are returned
+    assert_eq!(
+        plugs
+            .call::<i32, i32>("configured", "greeting", 64)
+            .unwrap(),
+        -1
+    );
+    assert_eq!(
+        plugs.call::<(), i64>("configured", "retries", ()).unwrap(),
+        -1
+    );
+    assert_eq!(
+        plugs.call::<(), f64>("configured", "ratio", ()).unwrap(),
+        0.5
+    );
+    assert_eq!(
+        plugs.call::<(), i32>("configured", "verbose", ()).unwrap(),
+        0
+    );
+
+    plugs
+        .set_config(
+            "configured",
+            [
+                ("greeting", "hello".into()),
+                ("retries", 3.into()),
+                ("ratio", 0.25.into()),
+                ("verbose", true.into()),
+            ],
+        )
+        .unwrap();
+    assert_eq!(
+        plugs
+            .call::<i32, i32>("configured", "greeting", 64)
+            .unwrap(),
+        5
+    );
+    assert_eq!(read_greeting(&mut plugs, 5), "hello");
+    assert_eq!(
+        plugs.call::<(), i64>("configured", "retries", ()).unwrap(),
+        3
+    );
+    assert_eq!(
+        plugs.call::<(), f64>("configured", "ratio", ()).unwrap(),
+        0.25
+    );
+    assert_eq!(
+        plugs.call::<(), i32>("configured", "verbose", ()).unwrap(),
+        1
+    );
+    assert_eq!(
+        plugs.get_config("configured", "retries"),
+        Some(ConfigValue::Int(3))
+    );
+}
+
+#[test]
+fn config_getters_check_types() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["configured"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    plugs
+        .set_config(
+            "configured",
+            [
+                ("greeting", "a long greeting".into()),
+                ("retries", "3".into()),
+                ("ratio", 2.into()),
+            ],
+        )
+        .unwrap();
+    // The value is truncated to the buffer but its full length is returned
+    assert_eq!(
+        plugs.call::<i32, i32>("configured", "greeting", 6).unwrap(),
+        15
+    );
+    assert_eq!(read_greeting(&mut plugs, 6), "a long");
+    assert_eq!(
+        plugs.call::<(), i64>("configured", "retries", ()).unwrap(),
+        -1
+    );
+    // Integers are accepted where floats are expected
+    assert_eq!(
+        plugs.call::<(), f64>("configured", "ratio", ()).unwrap(),
+        2.0
+    );
+}
+
+#[test]
+fn config_is_scoped_to_plugins() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base", "configured"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    plugs.set_config("base", [("retries", 9.into())]).unwrap();
+    assert_eq!(
+        plugs.call::<(), i64>("configured", "retries", ()).unwrap(),
+        -1
+    );
+
+    let err = plugs
+        .set_config("missing", [("retries", 9.into())])
+        .unwrap_err();
+    assert!(err.downcast_ref::<UnknownPlugin>().is_some());
+}
@@ -0,0 +1,135 @@
+mod common;
+
+use wlug::{
+    wasmtime::{Engine, Module},
+    ArgumentError, ExportNotFound, Plugs,
+};
+
+fn calc(engine: &Engine) -> Plugs<'static, common::Log> {
+    let mut plugs = common::plugs(engine);
+    let module = Module::new(
+        engine,
+        r#"(module
+            (memory (export "memory") 1)
+            (data (i32.const 0) "calc\00")
+            (func (export "__name") (result i32) i32.const 0)
+            (func (export "add") (param i32 i32) (result i32)
+                local.get 0 local.get 1 i32.add)
+            (func (export "widen") (param i32 i64) (result i64)
+                local.get 0 i64.extend_i32_s local.get 1 i64.add)
+            (func (export "scale") (param f32 f64) (result f32 f64)
+                local.get 0 local.get 1 f64.const 2 f64.mul)
+            (func (export "div") (param f64 f64) (result f64)
+                local.get 0 local.get 1 f64.div)
+            (func (export "null") (result funcref) ref.null func))"#,
+    )
+    .unwrap();
+    plugs.load_module(module, engine).unwrap();
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+    plugs
+}
+
+#[test]
+fn call_str_converts_arguments_and_results() {
+    let engine = Engine::default();
+    let mut plugs = calc(&engine);
+
+    assert_eq!(plugs.call_str("calc", "add", &["3", "4"]).unwrap(), ["7"]);
+    // Unsigned integers wrap around
+    assert_eq!(
+        plugs.call_str("calc", "add", &["4294967295", "2"]).unwrap(),
+        ["1"]
+    );
+    assert_eq!(
+        plugs.call_str("calc", "widen", &["-1", "5"]).unwrap(),
+        ["4"]
+    );
+    assert_eq!(
+        plugs.call_str("calc", "scale", &["0.1", "3"]).unwrap(),
+        ["0.1", "6"]
+    );
+}
+
+#[test]
+fn call_str_rejects_invalid_arguments() {
+    let engine = Engine::default();
+    let mut plugs = calc(&engine);
+
+    let err = plugs.call_str("calc", "add", &["3"]).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ArgumentError>(),
+        Some(ArgumentError::Count {
+            expected: 2,
+            found: 1,
+            ..
+        })
+    ));
+
+    let err = plugs.call_str("calc", "add", &["3", "4.5"]).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ArgumentError>(),
+        Some(ArgumentError::Invalid { index: 1, value, .. }) if value == "4.5"
+    ));
+
+    let err = plugs.call_str("calc", "null", &[]).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ArgumentError>(),
+        Some(ArgumentError::Unsupported { .. })
+    ));
+
+    let err = plugs.call_str("calc", "missing", &[]).unwrap_err();
+    assert!(err.downcast_ref::<ExportNotFound>().is_some());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn call_json_converts_arguments_and_results() {
+    use serde_json::json;
+
+    let engine = Engine::default();
+    let mut plugs = calc(&engine);
+
+    assert_eq!(
+        plugs.call_json("calc", "add", json!([3, "4"])).unwrap(),
+        json!([7])
+    );
+    // Floats without a fractional part are accepted for integers
+    assert_eq!(
+        plugs.call_json("calc", "add", json!([3.0, -4.0])).unwrap(),
+        json!([-1])
+    );
+    assert_eq!(
+        plugs.call_json("calc", "widen", json!([1, 1e12])).unwrap(),
+        json!([1_000_000_000_001_i64])
+    );
+    assert_eq!(
+        plugs.call_json("calc", "scale", json!([0.1, 3])).unwrap(),
+        json!([0.1, 6.0])
+    );
+    // Floats that JSON can't represent are returned as strings
+    assert_eq!(
+        plugs.call_json("calc", "div", json!([1, 0])).unwrap(),
+        json!(["inf"])
+    );
+
+    let err = plugs
+        .call_json("calc", "add", json!([3, true]))
+        .unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ArgumentError>(),
+        Some(ArgumentError::Invalid { index: 1, value, .. }) if value == "true"
+    ));
+    for (arg, value) in [(json!(3.5), "3.5"), (json!(1e10), "10000000000.0")] {
+        let err = plugs.call_json("calc", "add", json!([1, arg])).unwrap_err();
+        assert!(matches!(
+            err.downcast_ref::<ArgumentError>(),
+            Some(ArgumentError::Invalid { index: 1, value: v, .. }) if v == value
+        ));
+    }
+    let err = plugs.call_json("calc", "add", json!(3)).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ArgumentError>(),
+        Some(ArgumentError::Count { found: 1, .. })
+    ));
+}
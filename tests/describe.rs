@@ -0,0 +1,116 @@
+mod common;
+
+use wlug::{
+    wasmtime::{Engine, Module},
+    ExportDescription, ImportDescription, ItemType, ValueType,
+};
+
+#[test]
+fn describe_returns_typed_exports_and_imports() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base", "user"]);
+
+    let user = plugs.describe("user").unwrap();
+    assert_eq!(user.name, "user");
+    assert_eq!(
+        user.imports,
+        [
+            ImportDescription {
+                module: "env".to_string(),
+                name: "log".to_string(),
+                ty: ItemType::Func {
+                    params: vec![ValueType::I32],
+                    results: vec![],
+                },
+            },
+            ImportDescription {
+                module: "base".to_string(),
+                name: "add".to_string(),
+                ty: ItemType::Func {
+                    params: vec![ValueType::I32, ValueType::I32],
+                    results: vec![ValueType::I32],
+                },
+            },
+        ]
+    );
+    assert_eq!(
+        user.exports[0],
+        ExportDescription {
+            name: "memory".to_string(),
+            ty: ItemType::Memory {
+                minimum: 1,
+                maximum: None,
+                page_size: 65536,
+                memory64: false,
+                shared: false,
+            },
+        }
+    );
+    let names = user
+        .exports
+        .iter()
+        .map(|e| e.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["memory", "__name", "__deps", "double_and_log"]);
+
+    assert!(plugs.describe("missing").is_none());
+}
+
+#[test]
+fn item_types_display_their_signature() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    let module = Module::new(
+        &engine,
+        r#"(module
+            (memory (export "memory") 1 2)
+            (table (export "table") 1 funcref)
+            (global (export "counter") (mut i64) (i64.const 0))
+            (data (i32.const 0) "typed\00")
+            (func (export "__name") (result i32) i32.const 0)
+            (func (export "pair") (param f32) (result f64 i32)
+                f64.const 0 i32.const 0))"#,
+    )
+    .unwrap();
+    plugs.load_module(module, &engine).unwrap();
+
+    let types = plugs
+        .describe("typed")
+        .unwrap()
+        .exports
+        .iter()
+        .map(|e| format!("{}: {}", e.name, e.ty))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        types,
+        [
+            "memory: memory [1, 2] pages",
+            "table: table (ref null func) [1, ..]",
+            "counter: global mut i64",
+            "__name: func() -> i32",
+            "pair: func(f32) -> (f64, i32)",
+        ]
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn descriptions_are_serializable() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base"]);
+
+    let json = serde_json::to_value(plugs.describe("base").unwrap()).unwrap();
+    assert_eq!(json["name"], "base");
+    assert_eq!(
+        json["imports"][0],
+        serde_json::json!({
+            "module": "env",
+            "name": "log",
+            "ty": { "kind": "func", "params": ["i32"], "results": [] },
+        })
+    );
+    assert_eq!(json["exports"][0]["ty"]["kind"], "memory");
+    assert_eq!(json["exports"][0]["ty"]["maximum"], serde_json::Value::Null);
+}
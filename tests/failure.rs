@@ -0,0 +1,160 @@
+mod common;
+
+use wlug::{
+    wasmtime::{Caller, Engine, Module},
+    FailurePolicy, PlugContext, PlugStatus, PlugTrap, PluginDisabled, Plugs,
+};
+
+fn flaky_plugs(engine: &Engine, policy: FailurePolicy) -> Plugs<'static, common::Log> {
+    let mut plugs = common::plugs(engine).with_failure_policy(policy);
+    common::load(&mut plugs, engine, &["flaky", "flaky_user"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+    plugs
+}
+
+#[test]
+fn ignore_keeps_using_the_plugin() {
+    let engine = Engine::default();
+    let mut plugs = flaky_plugs(&engine, FailurePolicy::Ignore);
+
+    assert_eq!(plugs.call::<(), i32>("flaky", "bump", ()).unwrap(), 1);
+    assert!(plugs.call::<(), i32>("flaky", "crash", ()).is_err());
+    assert_eq!(plugs.call::<(), i32>("flaky", "bump", ()).unwrap(), 2);
+    assert_eq!(
+        plugs.get_plug("flaky").unwrap().status(),
+        &PlugStatus::Initialized
+    );
+}
+
+#[test]
+fn disable_rejects_further_calls() {
+    let engine = Engine::default();
+    let mut plugs = flaky_plugs(&engine, FailurePolicy::Disable);
+
+    assert!(plugs.call::<(), i32>("flaky", "crash", ()).is_err());
+    assert_eq!(
+        plugs.get_plug("flaky").unwrap().status(),
+        &PlugStatus::Disabled
+    );
+
+    let err = plugs.call::<(), i32>("flaky", "bump", ()).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<PluginDisabled>().unwrap().name(),
+        "flaky"
+    );
+    let err = plugs.call_dynamic("flaky", "bump", &[]).unwrap_err();
+    assert!(err.downcast_ref::<PluginDisabled>().is_some());
+
+    // `flaky_user` is linked to the disabled plugin, so it is disabled as well
+    assert_eq!(
+        plugs.get_plug("flaky_user").unwrap().status(),
+        &PlugStatus::Disabled
+    );
+    let err = plugs.call::<(), i32>("flaky_user", "run", ()).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<PluginDisabled>().unwrap().name(),
+        "flaky_user"
+    );
+
+    // Enabling the plugin doesn't enable its dependents
+    plugs.enable("flaky").unwrap();
+    assert_eq!(plugs.call::<(), i32>("flaky", "bump", ()).unwrap(), 1);
+    assert_eq!(
+        plugs.get_plug("flaky_user").unwrap().status(),
+        &PlugStatus::Disabled
+    );
+}
+
+#[test]
+fn restart_instantiates_the_plugin_and_its_dependents_again() {
+    let engine = Engine::default();
+    let mut plugs = flaky_plugs(&engine, FailurePolicy::Restart { max_restarts: 1 });
+    assert_eq!(plugs.state(), &[1, 2]);
+    assert_eq!(plugs.call::<(), i32>("flaky", "bump", ()).unwrap(), 1);
+    assert_eq!(plugs.call::<(), i32>("flaky", "bump", ()).unwrap(), 2);
+
+    // The trap happens in `flaky`, so it is restarted along with `flaky_user`
+    assert!(plugs.call::<(), i32>("flaky_user", "run", ()).is_err());
+    let flaky = plugs.get_plug("flaky").unwrap();
+    assert_eq!(flaky.restarts(), 1);
+    assert_eq!(flaky.status(), &PlugStatus::Initialized);
+    assert_eq!(plugs.get_plug("flaky_user").unwrap().restarts(), 0);
+    assert_eq!(plugs.state(), &[1, 2, 1, 2]);
+    assert_eq!(plugs.call::<(), i32>("flaky", "bump", ()).unwrap(), 1);
+
+    // The restarted dependent is linked to the new instance
+    assert!(plugs.call::<(), i32>("flaky_user", "run", ()).is_err());
+    assert_eq!(
+        plugs.get_plug("flaky").unwrap().status(),
+        &PlugStatus::Disabled
+    );
+    assert_eq!(
+        plugs.get_plug("flaky_user").unwrap().status(),
+        &PlugStatus::Disabled
+    );
+    assert_eq!(plugs.state(), &[1, 2, 1, 2]);
+}
+
+#[test]
+fn failure_policy_can_be_set_per_plugin() {
+    let engine = Engine::default();
+    let mut plugs = flaky_plugs(&engine, FailurePolicy::Ignore);
+    plugs.get_plug_mut("flaky").unwrap().failure_policy = FailurePolicy::Disable;
+
+    assert!(plugs.call::<(), i32>("flaky_user", "run", ()).is_err());
+    assert_eq!(
+        plugs.get_plug("flaky").unwrap().status(),
+        &PlugStatus::Disabled
+    );
+}
+
+#[test]
+fn traps_during_a_restart_disable_the_plugin() {
+    let engine = Engine::default();
+    let mut plugs = Plugs::new(&engine, 0)
+        .with_failure_policy(FailurePolicy::Restart { max_restarts: 3 })
+        .with_stats(true);
+    // Counts the calls to `__init`, which traps once it was called before
+    plugs.add_host_fn(
+        "init_count",
+        |mut caller: Caller<'_, PlugContext<i32>>| -> i32 {
+            caller.data_mut().1 += 1;
+            caller.data().1
+        },
+    );
+    let module = Module::new(
+        &engine,
+        r#"(module
+            (import "env" "init_count" (func $init_count (result i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "fragile\00")
+            (func (export "__name") (result i32) i32.const 0)
+            (func (export "__init")
+                call $init_count i32.const 1 i32.gt_s
+                if unreachable end)
+            (func (export "crash") unreachable))"#,
+    )
+    .unwrap();
+    plugs.load_module(module, &engine).unwrap();
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    assert!(plugs.call::<(), ()>("fragile", "crash", ()).is_err());
+
+    // The trap in `__init` doesn't start another restart
+    let fragile = plugs.get_plug("fragile").unwrap();
+    assert_eq!(fragile.restarts(), 1);
+    assert_eq!(fragile.status(), &PlugStatus::Disabled);
+    assert_eq!(*plugs.state(), 2);
+
+    // `__init` is called like any other export during a restart
+    let stats = plugs.stats().unwrap();
+    let init = &stats.plugs["fragile"].funcs["__init"];
+    assert_eq!(init.calls, 2);
+    assert_eq!(init.traps, 1);
+
+    let err = plugs.call::<(), ()>("fragile", "crash", ()).unwrap_err();
+    assert!(err.downcast_ref::<PluginDisabled>().is_some());
+    assert!(err.downcast_ref::<PlugTrap>().is_none());
+}
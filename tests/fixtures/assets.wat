@@ -0,0 +1,17 @@
+;; Reads the assets and configuration of the archive it is loaded from
+(module
+  (import "wlug" "asset_size" (func $asset_size (param i32 i32) (result i32)))
+  (import "wlug" "asset_read" (func $asset_read (param i32 i32 i32 i32 i32) (result i32)))
+  (import "wlug" "config_get_int" (func $get_int (param i32 i32 i64) (result i64)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "assets\00")
+  (data (i32.const 16) "greeting.txt")
+  (data (i32.const 32) "missing")
+  (data (i32.const 48) "level")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "greeting_size") (result i32) i32.const 16 i32.const 12 call $asset_size)
+  (func (export "missing_size") (result i32) i32.const 32 i32.const 7 call $asset_size)
+  ;; Copies part of `greeting.txt` to offset 128 and returns the number of copied bytes
+  (func (export "read_greeting") (param $offset i32) (param $len i32) (result i32)
+    i32.const 16 i32.const 12 local.get $offset i32.const 128 local.get $len call $asset_read)
+  (func (export "level") (result i64) i32.const 48 i32.const 5 i64.const 0 call $get_int))
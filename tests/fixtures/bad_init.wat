@@ -0,0 +1,6 @@
+;; Traps in its init export
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "bad_init\00")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "__init") unreachable))
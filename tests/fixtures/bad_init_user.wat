@@ -0,0 +1,9 @@
+;; Depends on `bad_init` and logs 2 in its init export
+(module
+  (import "env" "log" (func $log (param i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "bad_init_user\00")
+  (data (i32.const 16) "bad_init\00")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "__deps") (result i32) i32.const 16)
+  (func (export "__init") i32.const 2 call $log))
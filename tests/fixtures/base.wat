@@ -0,0 +1,12 @@
+;; A plugin without dependencies that has init and reset exports
+(module
+  (import "env" "log" (func $log (param i32)))
+  (memory (export "memory") 1)
+  (global $initialized (mut i32) (i32.const 0))
+  (data (i32.const 0) "base\00")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "__init") i32.const 1 global.set $initialized)
+  (func (export "__reset") i32.const -1 call $log)
+  (func (export "initialized") (result i32) global.get $initialized)
+  (func (export "add") (param i32 i32) (result i32)
+    local.get 0 local.get 1 i32.add))
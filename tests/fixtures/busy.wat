@@ -0,0 +1,13 @@
+;; Loops as many times as it is asked to
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "busy\00")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "spin") (param $n i32) (result i32)
+    (loop $again
+      local.get $n
+      i32.const 1
+      i32.sub
+      local.tee $n
+      br_if $again)
+    local.get $n))
@@ -0,0 +1,10 @@
+;; Calls `fail` in `faulty`
+(module
+  (import "faulty" "fail" (func $fail (param i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "chain\00")
+  (data (i32.const 16) "faulty\00")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "__deps") (result i32) i32.const 16)
+  (func $run (export "run") (param i32) (result i32)
+    local.get 0 call $fail))
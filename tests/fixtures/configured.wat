@@ -0,0 +1,21 @@
+;; Reads its configuration with the builtin config functions, has the version 1.2.0
+(module
+  (import "wlug" "config_get_str" (func $get_str (param i32 i32 i32 i32) (result i32)))
+  (import "wlug" "config_get_int" (func $get_int (param i32 i32 i64) (result i64)))
+  (import "wlug" "config_get_float" (func $get_float (param i32 i32 f64) (result f64)))
+  (import "wlug" "config_get_bool" (func $get_bool (param i32 i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "configured\00")
+  (data (i32.const 32) "greeting")
+  (data (i32.const 48) "retries")
+  (data (i32.const 64) "ratio")
+  (data (i32.const 80) "verbose")
+  (data (i32.const 96) "1.2.0\00")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "__version") (result i32) i32.const 96)
+  ;; Copies `greeting` to offset 128 and returns its length
+  (func (export "greeting") (param $buf_len i32) (result i32)
+    i32.const 32 i32.const 8 i32.const 128 local.get $buf_len call $get_str)
+  (func (export "retries") (result i64) i32.const 48 i32.const 7 i64.const -1 call $get_int)
+  (func (export "ratio") (result f64) i32.const 64 i32.const 5 f64.const 0.5 call $get_float)
+  (func (export "verbose") (result i32) i32.const 80 i32.const 7 i32.const 0 call $get_bool))
@@ -0,0 +1,9 @@
+;; Declares a dependency on `flaky` without importing anything from it, so it can be loaded before `flaky`
+(module
+  (import "env" "log" (func $log (param i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "early\00")
+  (data (i32.const 16) "flaky\00")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "__deps") (result i32) i32.const 16)
+  (func (export "__init") i32.const 3 call $log))
@@ -0,0 +1,7 @@
+;; Exports a function that always traps
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "faulty\00")
+  (func (export "__name") (result i32) i32.const 0)
+  (func $fail (export "fail") (param i32) (result i32)
+    unreachable))
@@ -0,0 +1,11 @@
+;; Has state that is lost when it's instantiated again and a function that traps
+(module
+  (import "env" "log" (func $log (param i32)))
+  (memory (export "memory") 1)
+  (global $counter (mut i32) (i32.const 0))
+  (data (i32.const 0) "flaky\00")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "__init") i32.const 1 call $log)
+  (func (export "bump") (result i32)
+    global.get $counter i32.const 1 i32.add global.set $counter global.get $counter)
+  (func (export "crash") (result i32) unreachable))
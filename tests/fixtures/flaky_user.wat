@@ -0,0 +1,11 @@
+;; Depends on `flaky` and calls its `crash` function
+(module
+  (import "env" "log" (func $log (param i32)))
+  (import "flaky" "crash" (func $crash (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "flaky_user\00")
+  (data (i32.const 16) "flaky\00")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "__deps") (result i32) i32.const 16)
+  (func (export "__init") i32.const 2 call $log)
+  (func (export "run") (result i32) call $crash))
@@ -0,0 +1,7 @@
+;; Depends on a plugin that doesn't exist
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "missing_dep\00")
+  (data (i32.const 16) "nowhere\00")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "__deps") (result i32) i32.const 16))
@@ -0,0 +1,8 @@
+;; Imports a function from `base` that `base` doesn't export
+(module
+  (import "base" "sub" (func (param i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "missing_export\00")
+  (data (i32.const 16) "base\00")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "__deps") (result i32) i32.const 16))
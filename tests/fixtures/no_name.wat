@@ -0,0 +1,3 @@
+;; Doesn't have a name export
+(module
+  (memory (export "memory") 1))
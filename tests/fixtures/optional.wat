@@ -0,0 +1,12 @@
+;; Has an optional dependency on `extra` which isn't one of the fixtures
+(module
+  (import "extra" "hook" (func $hook (param i32) (result i32)))
+  (import "wlug" "has_dep" (func $has_dep (param i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "optional\00")
+  (data (i32.const 16) "?extra;base\00")
+  (data (i32.const 32) "extra")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "__deps") (result i32) i32.const 16)
+  (func (export "has_extra") (result i32) i32.const 32 i32.const 5 call $has_dep)
+  (func (export "call_hook") (param i32) (result i32) local.get 0 call $hook))
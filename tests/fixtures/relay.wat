@@ -0,0 +1,7 @@
+;; Depends on `user` without importing anything from it
+(module
+  (memory (export "memory") 1)
+  (data (i32.const 0) "relay\00")
+  (data (i32.const 16) "user\00")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "__deps") (result i32) i32.const 16))
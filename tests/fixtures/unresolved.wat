@@ -0,0 +1,6 @@
+;; Imports a host function that isn't defined by the tests
+(module
+  (import "env" "undefined_fn" (func (param i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "unresolved\00")
+  (func (export "__name") (result i32) i32.const 0))
@@ -0,0 +1,11 @@
+;; Depends on `base` and imports `add` from it
+(module
+  (import "env" "log" (func $log (param i32)))
+  (import "base" "add" (func $add (param i32 i32) (result i32)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "user\00")
+  (data (i32.const 16) "base\00")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "__deps") (result i32) i32.const 16)
+  (func (export "double_and_log") (param i32) (result i32) (local i32)
+    local.get 0 local.get 0 call $add local.tee 1 call $log local.get 1))
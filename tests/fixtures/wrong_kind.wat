@@ -0,0 +1,8 @@
+;; Imports `add` from `base` as a global although it is a function
+(module
+  (import "base" "add" (global i32))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "wrong_kind\00")
+  (data (i32.const 16) "base\00")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "__deps") (result i32) i32.const 16))
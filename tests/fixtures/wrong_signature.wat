@@ -0,0 +1,8 @@
+;; Imports `add` from `base` with the wrong signature
+(module
+  (import "base" "add" (func (param i64 i64) (result i64)))
+  (memory (export "memory") 1)
+  (data (i32.const 0) "wrong_signature\00")
+  (data (i32.const 16) "base\00")
+  (func (export "__name") (result i32) i32.const 0)
+  (func (export "__deps") (result i32) i32.const 16))
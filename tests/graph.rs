@@ -0,0 +1,148 @@
+mod common;
+
+use wlug::{
+    wasmtime::{Engine, Module},
+    ExportType, Plugs,
+};
+
+/// A plugin whose name needs to be escaped in DOT and JSON, it imports `add` from `base`
+fn odd_name(engine: &Engine) -> Module {
+    Module::new(
+        engine,
+        r#"(module
+            (import "base" "add" (func (param i32 i32) (result i32)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "odd \22name\22\5c\0aline\00")
+            (data (i32.const 32) "base\00")
+            (func (export "__name") (result i32) i32.const 0)
+            (func (export "__deps") (result i32) i32.const 32))"#,
+    )
+    .unwrap()
+}
+
+fn graph_plugs(engine: &Engine) -> Plugs<'static, common::Log> {
+    let mut plugs = common::plugs(engine);
+    common::load(
+        &mut plugs,
+        engine,
+        &["base", "user", "optional", "missing_dep"],
+    );
+    plugs.load_module(odd_name(engine), engine).unwrap();
+    plugs
+}
+
+#[test]
+fn dependency_graph_contains_deps_and_symbols() {
+    let engine = Engine::default();
+    let plugs = graph_plugs(&engine);
+    let graph = plugs.dependency_graph();
+
+    let nodes = graph
+        .nodes
+        .iter()
+        .map(|node| (node.id, node.name.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        nodes,
+        [
+            (0, "base"),
+            (1, "user"),
+            (2, "optional"),
+            (3, "missing_dep"),
+            (4, "odd \"name\"\\\nline")
+        ]
+    );
+
+    let deps = graph
+        .deps
+        .iter()
+        .map(|edge| (edge.from, edge.to, edge.dep.as_str(), edge.optional))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        deps,
+        [
+            (1, Some(0), "base", false),
+            (2, None, "extra", true),
+            (2, Some(0), "base", false),
+            (3, None, "nowhere", false),
+            (4, Some(0), "base", false),
+        ]
+    );
+
+    let symbols = graph
+        .symbols
+        .iter()
+        .map(|sym| {
+            (
+                sym.from,
+                sym.to,
+                sym.module.as_str(),
+                sym.name.as_str(),
+                sym.kind,
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        symbols,
+        [
+            (1, 0, "base", "add", ExportType::Func),
+            (4, 0, "base", "add", ExportType::Func)
+        ]
+    );
+}
+
+#[test]
+fn dependency_graph_to_dot() {
+    let engine = Engine::default();
+    let plugs = graph_plugs(&engine);
+
+    let expected = r#"digraph plugs {
+    "base";
+    "user";
+    "optional";
+    "missing_dep";
+    "odd \"name\"\\\nline";
+    "extra" [style=dashed, color=red];
+    "nowhere" [style=dashed, color=red];
+    "user" -> "base" [label="add"];
+    "optional" -> "extra" [style=dashed];
+    "optional" -> "base";
+    "missing_dep" -> "nowhere";
+    "odd \"name\"\\\nline" -> "base" [label="add"];
+}
+"#;
+    assert_eq!(plugs.dependency_graph().to_dot(), expected);
+}
+
+#[test]
+#[cfg(feature = "json")]
+fn dependency_graph_to_json() {
+    let engine = Engine::default();
+    let plugs = graph_plugs(&engine);
+
+    let json: serde_json::Value =
+        serde_json::from_str(&plugs.dependency_graph().to_json()).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "nodes": [
+                { "id": 0, "name": "base" },
+                { "id": 1, "name": "user" },
+                { "id": 2, "name": "optional" },
+                { "id": 3, "name": "missing_dep" },
+                { "id": 4, "name": "odd \"name\"\\\nline" },
+            ],
+            "deps": [
+                { "from": 1, "to": 0, "dep": "base", "optional": false },
+                { "from": 2, "to": null, "dep": "extra", "optional": true },
+                { "from": 2, "to": 0, "dep": "base", "optional": false },
+                { "from": 3, "to": null, "dep": "nowhere", "optional": false },
+                { "from": 4, "to": 0, "dep": "base", "optional": false },
+            ],
+            "symbols": [
+                { "from": 1, "to": 0, "module": "base", "name": "add", "kind": "function" },
+                { "from": 4, "to": 0, "module": "base", "name": "add", "kind": "function" },
+            ],
+        })
+    );
+}
@@ -0,0 +1,329 @@
+mod common;
+
+use wlug::{
+    wasmtime::{Engine, Module, Trap},
+    DependencyFailed, ExportNotFound, InitError, InvalidPlugStatus, PlugStatus, PlugTrap,
+    PluginDisabled, PlugsResetOptions, TypeMismatchError, UnknownPlugin, Val,
+};
+
+#[test]
+fn init_calls_init_exports() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base", "user"]);
+    plugs.link().unwrap();
+
+    plugs.init().unwrap();
+    assert_eq!(plugs.call::<(), i32>("base", "initialized", ()).unwrap(), 1);
+}
+
+#[test]
+fn status_follows_lifecycle() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base", "user"]);
+    assert_eq!(
+        plugs.get_plug("user").unwrap().status(),
+        &PlugStatus::Loaded
+    );
+
+    let err = plugs.init().unwrap_err();
+    let err = err.downcast_ref::<InitError>().unwrap();
+    let err = err.error().downcast_ref::<InvalidPlugStatus>().unwrap();
+    assert_eq!(err.status(), &PlugStatus::Loaded);
+    assert_eq!(err.expected(), &PlugStatus::Linked);
+
+    // The status is checked before the plugin's exports are looked up
+    for func in ["double_and_log", "missing"] {
+        let err = plugs.call::<i32, i32>("user", func, 1).unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<InvalidPlugStatus>().unwrap().status(),
+            &PlugStatus::Loaded
+        );
+    }
+
+    plugs.link().unwrap();
+    assert_eq!(
+        plugs.get_plug("user").unwrap().status(),
+        &PlugStatus::Linked
+    );
+    let err = plugs
+        .call::<i32, i32>("user", "double_and_log", 1)
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<InvalidPlugStatus>().unwrap().status(),
+        &PlugStatus::Linked
+    );
+
+    plugs.init().unwrap();
+    assert!(plugs
+        .items()
+        .iter()
+        .all(|p| p.status() == &PlugStatus::Initialized));
+
+    // Initialized plugins are skipped
+    plugs.init().unwrap();
+    assert_eq!(plugs.call::<(), i32>("base", "initialized", ()).unwrap(), 1);
+}
+
+#[test]
+fn get_func_checks_status() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base"]);
+    plugs.link().unwrap();
+
+    let err = plugs
+        .get_func::<(i32, i32), i32>("base", "add")
+        .err()
+        .unwrap();
+    assert_eq!(
+        err.downcast_ref::<InvalidPlugStatus>().unwrap().status(),
+        &PlugStatus::Linked
+    );
+
+    plugs.init().unwrap();
+    let (id, add) = plugs.get_func::<(i32, i32), i32>("base", "add").unwrap();
+    assert_eq!(add.call(&mut plugs.store, (1, 2)).unwrap(), 3);
+
+    plugs.disable("base").unwrap();
+    let err = plugs
+        .get_func_by_id::<(i32, i32), i32>(id, "add")
+        .err()
+        .unwrap();
+    assert!(err.is::<PluginDisabled>());
+}
+
+#[test]
+fn init_runs_in_dependency_order() {
+    let engine = Engine::default();
+    for _ in 0..8 {
+        let mut plugs = common::plugs(&engine);
+        common::load(&mut plugs, &engine, &["early", "flaky", "flaky_user"]);
+        plugs.link().unwrap();
+
+        plugs.init().unwrap();
+        assert_eq!(plugs.state(), &[1, 3, 2]);
+    }
+}
+
+#[test]
+fn init_reports_failing_plugin() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["bad_init", "flaky"]);
+    plugs.link().unwrap();
+
+    let err = plugs.init().unwrap_err();
+    let err = err.downcast_ref::<InitError>().unwrap();
+    assert_eq!(err.plug_name(), "bad_init");
+    assert_eq!(
+        err.error().downcast_ref::<PlugTrap>().unwrap().trap(),
+        Some(Trap::UnreachableCodeReached)
+    );
+    // `flaky` comes after `bad_init` so it wasn't initialized
+    assert!(plugs.state().is_empty());
+    assert!(matches!(
+        plugs.get_plug("bad_init").unwrap().status(),
+        PlugStatus::Failed(_)
+    ));
+    assert_eq!(
+        plugs.get_plug("flaky").unwrap().status(),
+        &PlugStatus::Linked
+    );
+}
+
+#[test]
+fn init_all_continues_after_errors() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["bad_init", "flaky"]);
+    plugs.link().unwrap();
+
+    let errors = plugs.init_all().unwrap_err();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].plug_name(), "bad_init");
+    assert_eq!(plugs.state(), &[1]);
+}
+
+#[test]
+fn init_all_fails_dependents_of_failed_plugins() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["bad_init_user", "bad_init"]);
+    plugs.link().unwrap();
+
+    let errors = plugs.init_all().unwrap_err();
+    assert_eq!(errors.len(), 2);
+    assert_eq!(errors[0].plug_name(), "bad_init");
+    assert_eq!(errors[1].plug_name(), "bad_init_user");
+    let err = errors[1]
+        .error()
+        .downcast_ref::<DependencyFailed>()
+        .unwrap();
+    assert_eq!(err.dep(), "bad_init");
+    assert!(matches!(
+        plugs.get_plug("bad_init_user").unwrap().status(),
+        PlugStatus::Failed(_)
+    ));
+    // The init export of the dependent wasn't called
+    assert!(plugs.state().is_empty());
+}
+
+#[test]
+fn failed_plugins_are_linked_again_after_a_reset() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    // Its init export traps until its config key `ready` is set
+    let module = Module::new(
+        &engine,
+        r#"(module
+            (import "wlug" "config_get_int" (func $get_int (param i32 i32 i64) (result i64)))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "bad_init\00")
+            (data (i32.const 16) "ready")
+            (func (export "__name") (result i32) i32.const 0)
+            (func (export "__init")
+                i32.const 16 i32.const 5 i64.const 0 call $get_int
+                i64.eqz if unreachable end))"#,
+    )
+    .unwrap();
+    plugs.load_module(module, &engine).unwrap();
+    common::load(&mut plugs, &engine, &["bad_init_user"]);
+    plugs.link().unwrap();
+    assert_eq!(plugs.init_all().unwrap_err().len(), 2);
+
+    // Failed plugins are skipped when linking
+    plugs.link().unwrap();
+    assert!(matches!(
+        plugs.get_plug("bad_init").unwrap().status(),
+        PlugStatus::Failed(_)
+    ));
+
+    plugs.set_config("bad_init", [("ready", 1.into())]).unwrap();
+    assert_eq!(
+        plugs.reset_failed("bad_init").unwrap(),
+        ["bad_init", "bad_init_user"]
+    );
+    assert_eq!(
+        plugs.get_plug("bad_init_user").unwrap().status(),
+        &PlugStatus::Loaded
+    );
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+    assert!(plugs
+        .items()
+        .iter()
+        .all(|p| p.status() == &PlugStatus::Initialized));
+    assert_eq!(plugs.state(), &[2]);
+
+    // Plugins that didn't fail aren't reset
+    assert!(plugs.reset_failed("bad_init").unwrap().is_empty());
+}
+
+#[test]
+fn reset_calls_reset_exports_and_unloads_plugins() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base", "user"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    plugs.reset().unwrap();
+    assert_eq!(plugs.state(), &[-1]);
+    assert!(plugs.items().is_empty());
+    assert!(plugs.names().is_empty());
+
+    // Host functions are kept, so the same plugins can be loaded again
+    common::load(&mut plugs, &engine, &["base", "user"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+    assert_eq!(
+        plugs.call::<i32, i32>("user", "double_and_log", 2).unwrap(),
+        4
+    );
+}
+
+#[test]
+fn reset_with_options_replaces_state_and_host_fns() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    plugs
+        .reset_with_options(PlugsResetOptions::new(true, Some(vec![7]), true))
+        .unwrap();
+    assert_eq!(plugs.state(), &[7]);
+    assert!(plugs.host_fns().is_empty());
+}
+
+#[test]
+fn call_dynamic_returns_results() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    let res = plugs
+        .call_dynamic("base", "add", &[Val::I32(2), Val::I32(3)])
+        .unwrap();
+    assert_eq!(res.len(), 1);
+    assert_eq!(res[0].unwrap_i32(), 5);
+}
+
+#[test]
+fn call_dynamic_checks_arguments() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    let err = plugs
+        .call_dynamic("base", "add", &[Val::I64(2), Val::I32(3)])
+        .unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<TypeMismatchError>().unwrap().func_name(),
+        "add"
+    );
+
+    let err = plugs
+        .call_dynamic("base", "add", &[Val::I32(2)])
+        .unwrap_err();
+    assert!(err.downcast_ref::<TypeMismatchError>().is_some());
+}
+
+#[test]
+fn call_dynamic_reports_unknown_functions() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    let err = plugs.call_dynamic("base", "sub", &[]).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<ExportNotFound>().unwrap().export_name(),
+        "sub"
+    );
+
+    let err = plugs.call_dynamic("nobody", "add", &[]).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<UnknownPlugin>(),
+        Some(UnknownPlugin::Name(name)) if name == "nobody"
+    ));
+}
+
+#[test]
+fn call_dynamic_requires_link() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base"]);
+
+    assert!(plugs
+        .call_dynamic("base", "add", &[Val::I32(2), Val::I32(3)])
+        .is_err());
+}
@@ -0,0 +1,197 @@
+mod common;
+
+use wlug::{wasmtime::Engine, ExportType, LinkError, HOST_PROVIDER};
+
+fn link_error(names: &[&str]) -> LinkError {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, names);
+
+    let err = plugs.link().unwrap_err();
+    err.downcast::<LinkError>()
+        .unwrap_or_else(|e| panic!("expected a LinkError, got: {e}"))
+}
+
+#[test]
+fn link_resolves_imports_from_deps() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base", "user"]);
+
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+    assert!(plugs.items().iter().all(|p| p.instance.is_some()));
+
+    let res = plugs
+        .call::<i32, i32>("user", "double_and_log", 21)
+        .unwrap();
+    assert_eq!(res, 42);
+    assert_eq!(plugs.state(), &[42]);
+}
+
+#[test]
+fn link_stubs_missing_optional_deps() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base", "optional"]);
+
+    let plan = plugs.plan_link();
+    assert!(plan.is_ok());
+    assert_eq!(
+        plan.get("optional").unwrap().missing_optional_deps,
+        ["extra"]
+    );
+
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+    assert_eq!(
+        plugs.call::<(), i32>("optional", "has_extra", ()).unwrap(),
+        0
+    );
+    assert!(plugs.call::<i32, i32>("optional", "call_hook", 1).is_err());
+}
+
+#[test]
+fn link_reports_missing_dependency() {
+    match link_error(&["missing_dep"]) {
+        LinkError::DependencyNotFound(dep) => assert_eq!(dep, "nowhere"),
+        e => panic!("unexpected error: {e}"),
+    }
+}
+
+#[test]
+fn link_reports_dependency_loaded_after_dependent() {
+    match link_error(&["user", "base"]) {
+        LinkError::NotInstantiated {
+            dep_name,
+            plug_name,
+        } => {
+            assert_eq!(dep_name, "base");
+            assert_eq!(plug_name, "user");
+        }
+        e => panic!("unexpected error: {e}"),
+    }
+}
+
+#[test]
+fn link_reports_missing_export() {
+    match link_error(&["base", "missing_export"]) {
+        LinkError::ExportNotFound {
+            dep_name,
+            export_name,
+            plug_name,
+        } => {
+            assert_eq!(dep_name, "base");
+            assert_eq!(export_name, "sub");
+            assert_eq!(plug_name, "missing_export");
+        }
+        e => panic!("unexpected error: {e}"),
+    }
+}
+
+#[test]
+fn link_reports_unresolved_imports() {
+    match link_error(&["unresolved"]) {
+        LinkError::UnresolvedImports {
+            plug_name,
+            unresolved_imports,
+        } => {
+            assert_eq!(plug_name, "unresolved");
+            assert_eq!(unresolved_imports.len(), 1);
+            assert_eq!(unresolved_imports[0].to_string(), "env.undefined_fn");
+        }
+        e => panic!("unexpected error: {e}"),
+    }
+}
+
+#[test]
+fn link_reports_signature_mismatch() {
+    match link_error(&["base", "wrong_signature"]) {
+        LinkError::SignatureMismatch {
+            plug,
+            import,
+            provider,
+            ..
+        } => {
+            assert_eq!(plug, "wrong_signature");
+            assert_eq!(import.to_string(), "base.add");
+            assert_eq!(provider, "base");
+        }
+        e => panic!("unexpected error: {e}"),
+    }
+}
+
+#[test]
+fn link_reports_kind_mismatch() {
+    match link_error(&["base", "wrong_kind"]) {
+        LinkError::KindMismatch {
+            plug,
+            provider,
+            expected,
+            found,
+            ..
+        } => {
+            assert_eq!(plug, "wrong_kind");
+            assert_eq!(provider, "base");
+            assert_eq!(expected, ExportType::Global);
+            assert_eq!(found, ExportType::Func);
+        }
+        e => panic!("unexpected error: {e}"),
+    }
+}
+
+#[test]
+fn link_reports_signature_mismatch_with_host() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    plugs.add_host_fn("undefined_fn", |_: i64| {});
+    common::load(&mut plugs, &engine, &["unresolved"]);
+
+    match plugs.link().unwrap_err().downcast::<LinkError>().unwrap() {
+        LinkError::SignatureMismatch { provider, .. } => assert_eq!(provider, HOST_PROVIDER),
+        e => panic!("unexpected error: {e}"),
+    }
+}
+
+#[test]
+fn plan_link_collects_every_error() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(
+        &mut plugs,
+        &engine,
+        &["base", "missing_dep", "unresolved", "wrong_kind"],
+    );
+
+    let plan = plugs.plan_link();
+    assert!(!plan.is_ok());
+    assert_eq!(plan.errors().count(), 3);
+    assert!(plan.get("base").unwrap().is_ok());
+}
+
+#[test]
+fn link_enforces_capabilities() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base", "user"]);
+    plugs.get_plug_mut("base").unwrap().capabilities = Some(vec!["env".to_string()]);
+    plugs.get_plug_mut("user").unwrap().capabilities = Some(vec!["env.print".to_string()]);
+
+    let plan = plugs.plan_link();
+    assert!(plan.get("base").unwrap().is_ok());
+    // Only host functions need capabilities, `base.add` comes from a dependency
+    let errors = &plan.get("user").unwrap().errors;
+    assert_eq!(errors.len(), 1);
+    let LinkError::CapabilityNotGranted { plug, import } = &errors[0] else {
+        panic!("expected CapabilityNotGranted, got: {}", errors[0]);
+    };
+    assert_eq!(plug, "user");
+    assert_eq!(import.to_string(), "env.log");
+
+    let err = plugs.link().unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<LinkError>(),
+        Some(LinkError::CapabilityNotGranted { .. })
+    ));
+    assert!(plugs.get_plug("user").unwrap().instance.is_none());
+}
@@ -0,0 +1,193 @@
+mod common;
+
+use wlug::{
+    wasmtime::{Engine, Module},
+    ExportNotFound, MetadataError, PluginAlreadyExists, MAX_METADATA_LEN,
+};
+
+/// A module with `name` at the offset `name_at` that its name export returns and whose deps export returns `deps`
+fn metadata_module(engine: &Engine, name_at: u32, name: &str, deps: &str) -> Module {
+    Module::new(
+        engine,
+        format!(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const {name_at}) "{name}")
+                (data (i32.const 8192) "{deps}")
+                (func (export "__name") (result i32) i32.const {name_at})
+                (func (export "__deps") (result i32) i32.const 8192))"#
+        ),
+    )
+    .unwrap()
+}
+
+fn metadata_error(engine: &Engine, name_at: u32, name: &str, deps: &str) -> MetadataError {
+    let mut plugs = common::plugs(engine);
+    let module = metadata_module(engine, name_at, name, deps);
+    let err = plugs.load_module(module, engine).unwrap_err();
+    assert!(plugs.items().is_empty());
+    err.downcast::<MetadataError>().unwrap()
+}
+
+#[test]
+fn load_reads_metadata() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    let ids = common::load(&mut plugs, &engine, &["base", "user", "optional"]);
+
+    assert_eq!(ids, [0, 1, 2]);
+    assert_eq!(plugs.get_id("user"), Some(1));
+    assert_eq!(plugs.get_name(2).map(String::as_str), Some("optional"));
+
+    let base = plugs.get_plug("base").unwrap();
+    assert!(base.deps.is_empty());
+    assert!(base.exports.contains(&"add".to_string()));
+
+    let user = plugs.get_plug("user").unwrap();
+    assert_eq!(user.deps, ["base"]);
+    assert!(user.optional_deps.is_empty());
+    let imports = user
+        .imports
+        .iter()
+        .map(|imp| imp.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(imports, ["env.log", "base.add"]);
+
+    let optional = plugs.get_plug("optional").unwrap();
+    assert_eq!(optional.deps, ["extra", "base"]);
+    assert_eq!(optional.optional_deps, ["extra"]);
+}
+
+#[test]
+fn load_rejects_duplicate_names() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base"]);
+
+    let err = plugs.load(common::fixture("base"), &engine).unwrap_err();
+    let err = err.downcast_ref::<PluginAlreadyExists>().unwrap();
+    assert_eq!(err.name(), "base");
+    assert_eq!(plugs.items().len(), 1);
+}
+
+#[test]
+fn load_requires_name_export() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+
+    let err = plugs.load(common::fixture("no_name"), &engine).unwrap_err();
+    let err = err.downcast_ref::<ExportNotFound>().unwrap();
+    assert_eq!(err.export_name(), "__name");
+    assert!(plugs.items().is_empty());
+}
+
+#[test]
+fn load_fails_for_missing_file() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+
+    assert!(plugs
+        .load(common::fixture("does_not_exist"), &engine)
+        .is_err());
+}
+
+#[test]
+fn load_decodes_utf8_metadata() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    let module = metadata_module(&engine, 0, r"pl\c3\bcg\00", r"base;?extra;\00");
+    plugs.load_module(module, &engine).unwrap();
+
+    let plug = plugs.get_plug("plüg").unwrap();
+    // Empty entries are ignored
+    assert_eq!(plug.deps, ["base", "extra"]);
+    assert_eq!(plug.optional_deps, ["extra"]);
+}
+
+#[test]
+fn load_reads_descriptive_metadata() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine).with_license("spdx");
+    let module = Module::new(
+        &engine,
+        r#"(module
+            (memory (export "memory") 1)
+            (data (i32.const 0) "described\00")
+            (data (i32.const 16) "Adds more enemies\00")
+            (data (i32.const 48) "Ada; Grace;\00")
+            (data (i32.const 64) "MIT\00")
+            (data (i32.const 80) "https://example.com\00")
+            (data (i32.const 112) "Apache-2.0\00")
+            (func (export "__name") (result i32) i32.const 0)
+            (func (export "__description") (result i32) i32.const 16)
+            (func (export "__authors") (result i32) i32.const 48)
+            (func (export "__license") (result i32) i32.const 64)
+            (func (export "spdx") (result i32) i32.const 112)
+            (func (export "__homepage") (result i32) i32.const 80))"#,
+    )
+    .unwrap();
+    plugs.load_module(module, &engine).unwrap();
+    common::load(&mut plugs, &engine, &["base"]);
+
+    let described = plugs.get_plug("described").unwrap();
+    assert_eq!(described.description.as_deref(), Some("Adds more enemies"));
+    assert_eq!(described.authors, ["Ada", "Grace"]);
+    assert_eq!(described.license.as_deref(), Some("Apache-2.0"));
+    assert_eq!(described.homepage.as_deref(), Some("https://example.com"));
+
+    let base = plugs.get_plug("base").unwrap();
+    assert_eq!(base.version, None);
+    assert_eq!(base.description, None);
+    assert!(base.authors.is_empty());
+    assert_eq!(base.license, None);
+    assert_eq!(base.homepage, None);
+}
+
+#[test]
+fn load_rejects_invalid_metadata_strings() {
+    let engine = Engine::default();
+
+    let mut plugs = common::plugs(&engine);
+    let module = Module::new(
+        &engine,
+        r#"(module
+            (memory (export "memory") 1)
+            (func (export "__name") (result i32) i32.const 65537))"#,
+    )
+    .unwrap();
+    let err = plugs.load_module(module, &engine).unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<MetadataError>(),
+        Some(MetadataError::OutOfBounds { ptr: 65537, .. })
+    ));
+
+    // No NUL terminator before the end of memory
+    let err = metadata_error(&engine, 65532, "plug", r"\00");
+    assert!(matches!(err, MetadataError::Unterminated { export } if export == "__name"));
+
+    // Terminated, but after the maximum length
+    let long = "a".repeat(MAX_METADATA_LEN + 1) + r"\00";
+    let err = metadata_error(&engine, 0, &long, r"\00");
+    assert!(matches!(err, MetadataError::Unterminated { .. }));
+
+    let err = metadata_error(&engine, 0, r"pl\ffg\00", r"\00");
+    assert!(matches!(err, MetadataError::InvalidUtf8 { .. }));
+}
+
+#[test]
+fn load_rejects_invalid_names() {
+    let engine = Engine::default();
+
+    for name in ["", "a;b", "?plug"] {
+        let err = metadata_error(&engine, 0, &format!(r"{name}\00"), r"\00");
+        assert!(
+            matches!(&err, MetadataError::InvalidName { export, name: found } if export == "__name" && found == name),
+            "{err}"
+        );
+    }
+
+    let err = metadata_error(&engine, 0, r"plug\00", r"base;??extra\00");
+    assert!(
+        matches!(err, MetadataError::InvalidName { export, name } if export == "__deps" && name == "?extra")
+    );
+}
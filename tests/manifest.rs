@@ -0,0 +1,218 @@
+#![cfg(all(feature = "manifest", feature = "wat"))]
+
+mod common;
+
+use std::path::Path;
+
+use wlug::{
+    wasmtime::{Config, Engine, Trap},
+    ConfigValue, LinkError, Manifest, ManifestError, PlugStatus, PlugTrap, Plugs, SkipReason,
+};
+
+fn manifest(name: &str) -> std::path::PathBuf {
+    common::fixture(name).with_extension("toml")
+}
+
+#[test]
+fn load_manifest_skips_and_reports_plugins() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    let report = plugs.load_manifest(manifest("plugins"), &engine).unwrap();
+
+    let loaded = report
+        .loaded
+        .iter()
+        .map(|id| plugs.get_name(*id).unwrap().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(loaded, ["base", "configured"]);
+    for name in loaded {
+        assert_eq!(
+            plugs.get_plug(name).unwrap().status(),
+            &PlugStatus::Initialized
+        );
+    }
+
+    let skipped = report
+        .skipped
+        .iter()
+        .map(|s| (s.path.as_path(), &s.reason))
+        .collect::<Vec<_>>();
+    assert_eq!(skipped.len(), 4);
+    assert_eq!(
+        skipped[0],
+        (
+            Path::new("user.wat"),
+            &SkipReason::CapabilityNotGranted {
+                import: "env.log".to_string()
+            }
+        )
+    );
+    assert_eq!(
+        skipped[1],
+        (
+            Path::new("faulty.wat"),
+            &SkipReason::VersionMismatch {
+                expected: "2.0.0".to_string(),
+                found: None
+            }
+        )
+    );
+    assert_eq!(skipped[2], (Path::new("flaky.wat"), &SkipReason::Disabled));
+    assert_eq!(skipped[3].0, Path::new("missing.wat"));
+    assert!(matches!(skipped[3].1, SkipReason::LoadFailed(_)));
+}
+
+#[test]
+fn dependents_of_skipped_plugins_are_skipped() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    let report = plugs
+        .load_manifest(manifest("skipped_deps"), &engine)
+        .unwrap();
+
+    let loaded = report
+        .loaded
+        .iter()
+        .map(|id| plugs.get_name(*id).unwrap().as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(loaded, ["configured"]);
+
+    let skipped = report
+        .skipped
+        .iter()
+        .map(|s| (s.path.to_str().unwrap(), s.reason.clone()))
+        .collect::<Vec<_>>();
+    let missing = |dep: &str| SkipReason::MissingDependency {
+        dep: dep.to_string(),
+    };
+    // `relay` comes before `user` in the manifest but is skipped because `user` is
+    assert_eq!(
+        skipped,
+        [
+            ("relay.wat", missing("user")),
+            ("base.wat", SkipReason::Disabled),
+            ("user.wat", missing("base")),
+        ]
+    );
+    assert!(plugs.get_plug("user").is_none());
+}
+
+#[test]
+fn manifest_config_is_applied() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    plugs.load_manifest(manifest("plugins"), &engine).unwrap();
+
+    assert_eq!(
+        plugs.get_plug("configured").unwrap().version.as_deref(),
+        Some("1.2.0")
+    );
+    assert_eq!(
+        plugs.get_config("configured", "greeting"),
+        Some(ConfigValue::Str("hi".to_string()))
+    );
+    assert_eq!(
+        plugs.call::<(), i64>("configured", "retries", ()).unwrap(),
+        3
+    );
+    assert_eq!(
+        plugs.call::<(), f64>("configured", "ratio", ()).unwrap(),
+        1.5
+    );
+    assert_eq!(
+        plugs.call::<(), i32>("configured", "verbose", ()).unwrap(),
+        1
+    );
+}
+
+#[test]
+fn manifest_fuel_limits_calls() {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config).unwrap();
+    let mut plugs = common::plugs(&engine);
+    plugs.store.set_fuel(1_000_000).unwrap();
+    plugs.load_manifest(manifest("limits"), &engine).unwrap();
+    assert_eq!(plugs.get_plug("busy").unwrap().fuel_limit, Some(1000));
+
+    let fuel = plugs.store.get_fuel().unwrap();
+    assert_eq!(plugs.call::<i32, i32>("busy", "spin", 10).unwrap(), 0);
+    let err = plugs.call::<i32, i32>("busy", "spin", 10_000).unwrap_err();
+    let trap = err.downcast_ref::<PlugTrap>().unwrap();
+    assert_eq!(trap.trap(), Some(Trap::OutOfFuel));
+    // Calls into plugins with a fuel limit don't use the fuel of the store
+    assert_eq!(plugs.store.get_fuel().unwrap(), fuel);
+
+    plugs.get_plug_mut("busy").unwrap().fuel_limit = None;
+    assert_eq!(plugs.call::<i32, i32>("busy", "spin", 10_000).unwrap(), 0);
+    assert!(plugs.store.get_fuel().unwrap() < fuel);
+}
+
+#[test]
+fn manifest_fuel_limits_need_fuel_consumption() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    let report = plugs.load_manifest(manifest("limits"), &engine).unwrap();
+    assert!(report.loaded.is_empty());
+    assert_eq!(report.skipped.len(), 1);
+    assert_eq!(report.skipped[0].reason, SkipReason::FuelNotEnabled);
+    assert!(plugs.get_plug("busy").is_none());
+}
+
+#[test]
+fn fuel_limits_without_fuel_consumption_fail_calls() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["busy"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    plugs.get_plug_mut("busy").unwrap().fuel_limit = Some(1000);
+    let err = plugs.call::<i32, i32>("busy", "spin", 3).unwrap_err();
+    assert!(format!("{err}").contains("fuel limit"), "{err}");
+}
+
+#[test]
+fn from_manifest_creates_plugs() {
+    let engine = Engine::default();
+    let (mut plugs, report) = Plugs::from_manifest(manifest("simple"), &engine, ()).unwrap();
+    assert_eq!(report.loaded.len(), 1);
+    assert!(report.skipped.is_empty());
+    assert_eq!(
+        plugs.call::<(), i64>("configured", "retries", ()).unwrap(),
+        -1
+    );
+}
+
+#[test]
+fn invalid_manifests_are_reported() {
+    let err = Manifest::parse("[[plugin]]\nenabled = true\n", "bad.toml").unwrap_err();
+    assert_eq!(err.path(), Path::new("bad.toml"));
+    assert!(err.message().contains("path"), "{}", err.message());
+
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    let err = plugs
+        .load_manifest(common::fixture("base"), &engine)
+        .unwrap_err();
+    assert!(err.downcast_ref::<ManifestError>().is_some());
+}
+
+#[test]
+fn manifest_capabilities_are_enforced_when_linking() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    plugs.load_manifest(manifest("plugins"), &engine).unwrap();
+    assert_eq!(
+        plugs.get_plug("base").unwrap().capabilities,
+        Some(vec!["env".to_string()])
+    );
+    assert_eq!(plugs.get_plug("configured").unwrap().capabilities, None);
+
+    plugs.get_plug_mut("base").unwrap().capabilities = Some(Vec::new());
+    let plan = plugs.plan_link();
+    assert!(matches!(
+        plan.get("base").unwrap().errors.as_slice(),
+        [LinkError::CapabilityNotGranted { .. }]
+    ));
+}
@@ -0,0 +1,180 @@
+mod common;
+
+use wlug::{
+    wasmtime::{Config, Engine, Error, Module},
+    Plugs,
+};
+
+#[test]
+fn stats_count_calls_and_host_calls() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine).with_stats(true);
+    common::load(&mut plugs, &engine, &["base", "user"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    for x in 1..=3 {
+        plugs.call::<i32, i32>("user", "double_and_log", x).unwrap();
+    }
+    plugs
+        .call::<(i32, i32), i32>("base", "add", (1, 2))
+        .unwrap();
+
+    let stats = plugs.stats().unwrap();
+    let user = &stats.plugs["user"];
+    assert_eq!(user.funcs["double_and_log"].calls, 3);
+    assert_eq!(user.funcs["double_and_log"].traps, 0);
+    assert_eq!(user.host_fns["env.log"].calls, 3);
+    assert!(user.funcs["double_and_log"].max_time <= user.funcs["double_and_log"].total_time);
+
+    let base = &stats.plugs["base"];
+    assert_eq!(base.funcs["__init"].calls, 1);
+    assert_eq!(base.funcs["add"].calls, 1);
+    // Calls from `user` to `base` don't go through the host
+    assert!(base.host_fns.is_empty());
+
+    plugs.reset_stats();
+    assert!(plugs.stats().unwrap().plugs.is_empty());
+}
+
+#[test]
+fn stats_are_disabled_by_default() {
+    let engine = Engine::default();
+    let plugs = common::plugs(&engine);
+    assert!(plugs.stats().is_none());
+}
+
+#[test]
+fn stats_count_traps() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine).with_stats(true);
+    common::load(&mut plugs, &engine, &["faulty"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    plugs.call::<i32, i32>("faulty", "fail", 0).unwrap_err();
+    plugs.call::<i32, i32>("faulty", "fail", 1).unwrap_err();
+
+    let stats = plugs.stats().unwrap();
+    let fail = &stats.plugs["faulty"].funcs["fail"];
+    assert_eq!(fail.calls, 2);
+    assert_eq!(fail.traps, 2);
+}
+
+#[test]
+fn stats_dont_count_host_errors_as_traps() {
+    let engine = Engine::default();
+    let mut plugs = Plugs::new(&engine, ()).with_stats(true);
+    plugs.add_host_fn("refuse", || -> Result<(), Error> {
+        Err(Error::msg("refused"))
+    });
+    let module = Module::new(
+        &engine,
+        r#"(module
+            (import "env" "refuse" (func $refuse))
+            (memory (export "memory") 1)
+            (data (i32.const 0) "refuser\00")
+            (func (export "__name") (result i32) i32.const 0)
+            (func (export "run") call $refuse))"#,
+    )
+    .unwrap();
+    plugs.load_module(module, &engine).unwrap();
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    plugs.call::<(), ()>("refuser", "run", ()).unwrap_err();
+
+    let stats = plugs.stats().unwrap();
+    let refuser = &stats.plugs["refuser"];
+    assert_eq!(refuser.funcs["run"].calls, 1);
+    assert_eq!(refuser.funcs["run"].traps, 0);
+    assert_eq!(refuser.host_fns["env.refuse"].calls, 1);
+    assert_eq!(refuser.host_fns["env.refuse"].traps, 0);
+}
+
+#[test]
+fn stats_record_fuel() {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    let engine = Engine::new(&config).unwrap();
+    let mut plugs = common::plugs(&engine).with_stats(true);
+    plugs.store.set_fuel(1_000_000).unwrap();
+    common::load(&mut plugs, &engine, &["busy"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    plugs.call::<i32, i32>("busy", "spin", 10).unwrap();
+    let short = plugs.stats().unwrap().plugs["busy"].funcs["spin"].fuel_used;
+    assert!(short > 0);
+
+    plugs.call::<i32, i32>("busy", "spin", 100).unwrap();
+    let total = plugs.stats().unwrap().plugs["busy"].funcs["spin"].fuel_used;
+    assert!(total - short > short);
+}
+
+#[test]
+fn stats_count_builtin_calls() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine).with_stats(true);
+    common::load(&mut plugs, &engine, &["configured"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+    plugs
+        .set_config("configured", [("retries", 3.into())])
+        .unwrap();
+
+    // The builtin still reads the config key from the plugin's memory when it's wrapped
+    assert_eq!(
+        plugs.call::<(), i64>("configured", "retries", ()).unwrap(),
+        3
+    );
+    let stats = plugs.stats().unwrap();
+    let configured = &stats.plugs["configured"];
+    assert_eq!(configured.host_fns["wlug.config_get_int"].calls, 1);
+    assert!(!configured.host_fns.contains_key("wlug.config_get_str"));
+}
+
+#[test]
+fn stats_to_prometheus() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine).with_stats(true);
+    common::load(&mut plugs, &engine, &["base", "user", "faulty"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+    plugs.call::<i32, i32>("user", "double_and_log", 1).unwrap();
+    plugs.call::<i32, i32>("faulty", "fail", 0).unwrap_err();
+
+    let text = plugs.stats().unwrap().to_prometheus();
+    let lines = text.lines().collect::<Vec<_>>();
+    assert!(lines.contains(&"# TYPE wlug_calls_total counter"));
+    assert!(lines.contains(&"wlug_calls_total{plug=\"user\",func=\"double_and_log\"} 1"));
+    assert!(lines.contains(&"wlug_calls_traps_total{plug=\"faulty\",func=\"fail\"} 1"));
+    assert!(lines.contains(&"wlug_calls_fuel_total{plug=\"user\",func=\"double_and_log\"} 0"));
+    assert!(lines.contains(&"# TYPE wlug_calls_seconds_max gauge"));
+    assert!(lines.contains(&"wlug_host_calls_total{plug=\"user\",func=\"env.log\"} 1"));
+    // Fuel is only measured for calls into plugins
+    assert!(!text.contains("wlug_host_calls_fuel_total"));
+
+    // Rows are sorted by plugin and function name
+    let calls = lines
+        .iter()
+        .filter(|line| line.starts_with("wlug_calls_total{"))
+        .collect::<Vec<_>>();
+    let mut sorted = calls.clone();
+    sorted.sort();
+    assert_eq!(calls, sorted);
+}
+
+#[test]
+fn prometheus_labels_are_escaped() {
+    let mut stats = wlug::Stats::default();
+    stats
+        .plugs
+        .entry("a\"b\\c\nd".to_string())
+        .or_default()
+        .funcs
+        .insert("f".to_string(), wlug::CallStats::default());
+
+    let text = stats.to_prometheus();
+    assert!(text.contains("wlug_calls_total{plug=\"a\\\"b\\\\c\\nd\",func=\"f\"} 0"));
+}
@@ -0,0 +1,66 @@
+mod common;
+
+use wlug::{
+    testing::{assert_exports, CallRecorder, FakePlug},
+    wasmtime::Engine,
+    Callee,
+};
+
+#[test]
+fn fake_plug_stands_in_for_a_dependency() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    FakePlug::new(&mut plugs, "base")
+        .func("add", |a: i32, b: i32| a * 100 + b)
+        .load(&engine)
+        .unwrap();
+    common::load(&mut plugs, &engine, &["user"]);
+
+    assert_exports(
+        plugs.get_plug("base").unwrap(),
+        &["add", "memory", "__name"],
+    );
+
+    let recorder = CallRecorder::new();
+    plugs.add_interceptor(recorder.clone());
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    assert_eq!(
+        plugs.call::<i32, i32>("user", "double_and_log", 3).unwrap(),
+        303
+    );
+
+    let adds = recorder.calls_to("base.add");
+    assert_eq!(adds.len(), 1);
+    assert_eq!(adds[0].caller, plugs.get_id("user"));
+    assert!(matches!(&adds[0].callee, Callee::Plug { id: 0, .. }));
+    let args = adds[0]
+        .args
+        .iter()
+        .map(|v| v.unwrap_i32())
+        .collect::<Vec<_>>();
+    assert_eq!(args, [3, 3]);
+
+    let logs = recorder.calls_to("env.log");
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].args[0].unwrap_i32(), 303);
+
+    let calls = recorder.calls_to("user.double_and_log");
+    assert_eq!(calls.len(), 1);
+    assert_eq!(calls[0].caller, None);
+    assert_eq!(calls[0].results.as_ref().unwrap()[0].unwrap_i32(), 303);
+
+    recorder.clear();
+    assert!(recorder.calls().is_empty());
+}
+
+#[test]
+#[should_panic(expected = "missing the exports [\"sub\"]")]
+fn assert_exports_panics_on_missing_exports() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base"]);
+
+    assert_exports(plugs.get_plug("base").unwrap(), &["add", "sub"]);
+}
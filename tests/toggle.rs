@@ -0,0 +1,113 @@
+mod common;
+
+use wlug::{wasmtime::Engine, PlugStatus, PluginDisabled, ToggleError};
+
+#[test]
+fn disabled_plugins_keep_their_memory() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["flaky"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    assert_eq!(plugs.call::<(), i32>("flaky", "bump", ()).unwrap(), 1);
+    plugs.disable("flaky").unwrap();
+    assert_eq!(
+        plugs.get_plug("flaky").unwrap().status(),
+        &PlugStatus::Disabled
+    );
+
+    let err = plugs.call::<(), i32>("flaky", "bump", ()).unwrap_err();
+    assert_eq!(
+        err.downcast_ref::<PluginDisabled>().unwrap().name(),
+        "flaky"
+    );
+    let err = plugs.call_dynamic("flaky", "bump", &[]).unwrap_err();
+    assert!(err.downcast_ref::<PluginDisabled>().is_some());
+
+    plugs.enable("flaky").unwrap();
+    assert_eq!(
+        plugs.get_plug("flaky").unwrap().status(),
+        &PlugStatus::Initialized
+    );
+    assert_eq!(plugs.call::<(), i32>("flaky", "bump", ()).unwrap(), 2);
+}
+
+#[test]
+fn disable_refuses_plugins_with_dependents() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base", "user"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    let err = plugs.disable("base").unwrap_err();
+    match err.downcast_ref::<ToggleError>().unwrap() {
+        ToggleError::HasDependents { name, dependents } => {
+            assert_eq!(name, "base");
+            assert_eq!(dependents, &["user"]);
+        }
+        e => panic!("unexpected error: {e}"),
+    }
+    assert_eq!(
+        plugs.get_plug("base").unwrap().status(),
+        &PlugStatus::Initialized
+    );
+
+    // Once the dependents are disabled the dependency can be disabled too
+    plugs.disable("user").unwrap();
+    plugs.disable("base").unwrap();
+}
+
+#[test]
+fn disable_with_dependents_cascades() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(
+        &mut plugs,
+        &engine,
+        &["early", "flaky", "flaky_user", "base"],
+    );
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    let disabled = plugs.disable_with_dependents("flaky").unwrap();
+    assert_eq!(disabled, ["flaky", "early", "flaky_user"]);
+    assert_eq!(
+        plugs.get_plug("base").unwrap().status(),
+        &PlugStatus::Initialized
+    );
+
+    let err = plugs.enable("flaky_user").unwrap_err();
+    assert!(matches!(
+        err.downcast_ref::<ToggleError>(),
+        Some(ToggleError::DependencyDisabled { dep, .. }) if dep == "flaky"
+    ));
+
+    plugs.enable("flaky").unwrap();
+    plugs.enable("flaky_user").unwrap();
+    assert_eq!(
+        plugs.get_plug("flaky_user").unwrap().status(),
+        &PlugStatus::Initialized
+    );
+}
+
+#[test]
+fn init_skips_disabled_plugins() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["flaky", "flaky_user"]);
+    plugs.link().unwrap();
+
+    plugs.disable("flaky_user").unwrap();
+    plugs.init().unwrap();
+    assert_eq!(plugs.state(), &[1]);
+
+    plugs.enable("flaky_user").unwrap();
+    assert_eq!(
+        plugs.get_plug("flaky_user").unwrap().status(),
+        &PlugStatus::Linked
+    );
+    plugs.init().unwrap();
+    assert_eq!(plugs.state(), &[1, 2]);
+}
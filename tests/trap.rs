@@ -0,0 +1,75 @@
+mod common;
+
+use wlug::{
+    testing::FakePlug,
+    wasmtime::{Engine, Trap},
+    PlugTrap,
+};
+
+#[test]
+fn trap_reports_entry_point_and_backtrace() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["faulty", "chain"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    let err = plugs.call::<i32, i32>("chain", "run", 1).unwrap_err();
+    let trap = err.downcast_ref::<PlugTrap>().unwrap();
+    assert_eq!(trap.plug_name(), "chain");
+    assert_eq!(trap.func_name(), "run");
+    assert_eq!(trap.trap(), Some(Trap::UnreachableCodeReached));
+
+    let frames = trap
+        .frames()
+        .iter()
+        .map(|frame| (frame.plug.as_deref(), frame.func_name.as_deref()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        frames,
+        [(Some("faulty"), Some("fail")), (Some("chain"), Some("run"))]
+    );
+
+    // The original trap is still available
+    assert_eq!(
+        err.downcast_ref::<Trap>(),
+        Some(&Trap::UnreachableCodeReached)
+    );
+    assert!(err.to_string().contains("0: faulty!fail"));
+}
+
+#[test]
+fn host_errors_are_reported_without_trap_code() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    FakePlug::new(&mut plugs, "base")
+        .func("add", |_: i32, _: i32| -> wasmtime::Result<i32> {
+            Err(wasmtime::Error::msg("add is broken"))
+        })
+        .load(&engine)
+        .unwrap();
+    common::load(&mut plugs, &engine, &["user"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    let err = plugs
+        .call_dynamic("user", "double_and_log", &[1.into()])
+        .unwrap_err();
+    let trap = err.downcast_ref::<PlugTrap>().unwrap();
+    assert_eq!(trap.plug_name(), "user");
+    assert_eq!(trap.trap(), None);
+    assert_eq!(trap.frames()[0].plug.as_deref(), Some("user"));
+    assert!(format!("{err:#}").contains("add is broken"));
+}
+
+#[test]
+fn errors_outside_of_wasm_are_unchanged() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    common::load(&mut plugs, &engine, &["base"]);
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+
+    let err = plugs.call_dynamic("base", "add", &[]).unwrap_err();
+    assert!(err.downcast_ref::<PlugTrap>().is_none());
+}
@@ -0,0 +1,152 @@
+#![cfg(feature = "verify")]
+
+use std::path::PathBuf;
+
+use ed25519_dalek::{Signer, SigningKey};
+use wlug::{
+    embed_signature, sha256, strip_signature, wasmtime::Engine, Plugs, Verification,
+    VerificationError,
+};
+
+/// A module named `tiny` with a memory and a name export
+const TINY: &[u8] = &[
+    0x00, 0x61, 0x73, 0x6d, 0x01, 0x00, 0x00, 0x00, // header
+    0x01, 0x05, 0x01, 0x60, 0x00, 0x01, 0x7f, // type: () -> i32
+    0x03, 0x02, 0x01, 0x00, // func
+    0x05, 0x03, 0x01, 0x00, 0x01, // memory: 1 page
+    0x07, 0x13, 0x02, 0x06, b'm', b'e', b'm', b'o', b'r', b'y', 0x02, 0x00, 0x06, b'_', b'_', b'n',
+    b'a', b'm', b'e', 0x00, 0x00, // exports
+    0x0a, 0x06, 0x01, 0x04, 0x00, 0x41, 0x00, 0x0b, // code: i32.const 0
+    0x0b, 0x0b, 0x01, 0x00, 0x41, 0x00, 0x0b, 0x05, b't', b'i', b'n', b'y',
+    0x00, // data: "tiny\0"
+];
+
+fn key(seed: u8) -> SigningKey {
+    SigningKey::from_bytes(&[seed; 32])
+}
+
+fn verification_error(err: wlug::wasmtime::Error) -> VerificationError {
+    err.downcast::<VerificationError>().unwrap()
+}
+
+#[test]
+fn sha256_digests_are_checked() {
+    let engine = Engine::default();
+    let mut plugs = Plugs::new(&engine, ());
+
+    let mut wrong = sha256(TINY);
+    wrong[0] ^= 1;
+    let err = plugs
+        .load_binary_verified(TINY, &Verification::Sha256(wrong), &engine)
+        .unwrap_err();
+    assert!(matches!(
+        verification_error(err),
+        VerificationError::DigestMismatch { found, .. } if found == sha256(TINY)
+    ));
+    assert!(plugs.items().is_empty());
+
+    plugs
+        .load_binary_verified(TINY, &Verification::Sha256(sha256(TINY)), &engine)
+        .unwrap();
+    assert!(plugs.get_plug("tiny").is_some());
+}
+
+#[test]
+fn embedded_signatures_are_checked() {
+    let engine = Engine::default();
+    let signature = key(1).sign(TINY).to_bytes();
+    let signed = embed_signature(TINY, &signature).unwrap();
+    assert_eq!(
+        strip_signature(&signed).unwrap(),
+        (TINY.to_vec(), Some(signature.to_vec()))
+    );
+
+    let mut plugs = Plugs::new(&engine, ()).with_trusted_key(key(2).verifying_key().to_bytes());
+    let err = plugs
+        .load_binary_verified(TINY, &Verification::Signature, &engine)
+        .unwrap_err();
+    assert!(matches!(
+        verification_error(err),
+        VerificationError::MissingSignature
+    ));
+    let err = plugs
+        .load_binary_verified(&signed, &Verification::Signature, &engine)
+        .unwrap_err();
+    assert!(matches!(
+        verification_error(err),
+        VerificationError::UntrustedSignature
+    ));
+
+    let mut plugs = plugs.with_trusted_key(key(1).verifying_key().to_bytes());
+    plugs
+        .load_binary_verified(&signed, &Verification::Signature, &engine)
+        .unwrap();
+}
+
+#[test]
+fn tampered_binaries_are_rejected() {
+    let engine = Engine::default();
+    let signature = key(1).sign(TINY).to_bytes();
+    let mut signed = embed_signature(TINY, &signature).unwrap();
+    // Rename the plugin to "tinz"
+    let pos = signed.windows(4).position(|w| w == b"tiny").unwrap();
+    signed[pos + 3] = b'z';
+
+    let mut plugs = Plugs::new(&engine, ()).with_trusted_key(key(1).verifying_key().to_bytes());
+    let err = plugs
+        .load_binary_verified(&signed, &Verification::Signature, &engine)
+        .unwrap_err();
+    assert!(matches!(
+        verification_error(err),
+        VerificationError::UntrustedSignature
+    ));
+
+    let err = plugs
+        .load_binary_verified(
+            b"not wasm",
+            &Verification::DetachedSignature(signature),
+            &engine,
+        )
+        .unwrap_err();
+    assert!(matches!(
+        verification_error(err),
+        VerificationError::MalformedModule
+    ));
+}
+
+#[test]
+fn detached_signature_files_are_used() {
+    let engine = Engine::default();
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("verify");
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("tiny.wasm");
+    std::fs::write(&path, TINY).unwrap();
+    let _ = std::fs::remove_file(dir.join("tiny.wasm.sig"));
+
+    let mut plugs = Plugs::new(&engine, ()).with_trusted_key(key(1).verifying_key().to_bytes());
+    let err = plugs
+        .load_verified(&path, &Verification::Signature, &engine)
+        .unwrap_err();
+    assert!(matches!(
+        verification_error(err),
+        VerificationError::MissingSignature
+    ));
+
+    std::fs::write(
+        dir.join("tiny.wasm.sig"),
+        &key(1).sign(TINY).to_bytes()[..63],
+    )
+    .unwrap();
+    let err = plugs
+        .load_verified(&path, &Verification::Signature, &engine)
+        .unwrap_err();
+    assert!(matches!(
+        verification_error(err),
+        VerificationError::MalformedSignature
+    ));
+
+    std::fs::write(dir.join("tiny.wasm.sig"), key(1).sign(TINY).to_bytes()).unwrap();
+    plugs
+        .load_verified(&path, &Verification::Signature, &engine)
+        .unwrap();
+}
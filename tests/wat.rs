@@ -0,0 +1,75 @@
+#![cfg(feature = "wat")]
+
+mod common;
+
+use wlug::{wasmtime::Engine, WatError};
+
+#[test]
+fn load_wat_loads_text_modules() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    let id = plugs
+        .load_wat(
+            r#"(module
+                (memory (export "memory") 1)
+                (data (i32.const 0) "inline\00")
+                (func (export "__name") (result i32) i32.const 0)
+                (func (export "answer") (result i32) i32.const 42))"#,
+            &engine,
+        )
+        .unwrap();
+
+    assert_eq!(plugs.get_name(id).map(String::as_str), Some("inline"));
+    plugs.link().unwrap();
+    plugs.init().unwrap();
+    assert_eq!(plugs.call::<(), i32>("inline", "answer", ()).unwrap(), 42);
+}
+
+#[test]
+fn load_wat_reports_line_and_column() {
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    let err = plugs
+        .load_wat("(module\n  (func (result i32)\n    i32.bogus))", &engine)
+        .unwrap_err();
+
+    let err = err.downcast_ref::<WatError>().unwrap();
+    assert_eq!((err.line(), err.column()), (3, 5));
+    assert!(err.path().is_none());
+    assert!(err
+        .to_string()
+        .starts_with("Couldn't parse WebAssembly text at <wat>:3:5"));
+}
+
+#[test]
+fn load_reports_path_of_wat_files() {
+    let dir = std::env::temp_dir().join(format!("wlug-wat-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("broken.wat");
+    std::fs::write(&path, "(module (memory 1)\n  (oops))").unwrap();
+
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    let err = plugs.load(&path, &engine).unwrap_err();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    let err = err.downcast_ref::<WatError>().unwrap();
+    assert_eq!(err.path(), Some(path.as_path()));
+    assert_eq!(err.line(), 2);
+}
+
+#[test]
+fn load_dir_loads_wat_files() {
+    let dir = std::env::temp_dir().join(format!("wlug-wat-dir-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::copy(common::fixture("base"), dir.join("0_base.wat")).unwrap();
+    std::fs::copy(common::fixture("user"), dir.join("1_user.wat")).unwrap();
+
+    let engine = Engine::default();
+    let mut plugs = common::plugs(&engine);
+    let ids = plugs.load_dir(&dir, &engine);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(ids.unwrap(), [0, 1]);
+    assert_eq!(plugs.get_id("user"), Some(1));
+}